@@ -3,8 +3,11 @@
 use std::fmt;
 
 use crate::{
+    Dimensions,
     Error,
     Expression,
+    Number,
+    dimension::round_power,
 };
 
 #[derive(Clone, Copy, Debug)]
@@ -17,6 +20,17 @@ pub enum BinaryOperation {
 }
 
 impl BinaryOperation {
+    /// Gets the precedence of this operation, which matches the precedence
+    /// of its token in the parser (higher binds more tightly).
+    pub fn precedence(&self) -> u8 {
+        use BinaryOperation::*;
+
+        match self {
+            Add | Sub => 3,
+            Mul | Div => 4,
+        }
+    }
+
     /// Displays this operation in LaTeX.
     pub fn latex(&self) -> String {
         use BinaryOperation::*;
@@ -31,28 +45,63 @@ impl BinaryOperation {
         oper.to_string()
     }
 
+    /// Displays this operation in MathML.
+    pub fn mathml(&self) -> String {
+        use BinaryOperation::*;
+
+        let oper = match self {
+            Add => "<mo>+</mo>",
+            Sub => "<mo>-</mo>",
+            Mul => "<mo>&#x2062;</mo>",
+            Div => "<mo>/</mo>",
+        };
+
+        oper.to_string()
+    }
+
+    /// Displays this operation in Typst.
+    pub fn typst(&self) -> String {
+        use BinaryOperation::*;
+
+        let oper = match self {
+            Add => " + ",
+            Sub => " - ",
+            Mul => " ",
+            Div => " / ",
+        };
+
+        oper.to_string()
+    }
+
     /// Simplifies this binary operation.
     pub fn simplify(&self, left: &Expression, right: &Expression) -> Expression {
         use Expression::*;
 
-        if let Float {
-            value: l_value,
-            kg: l_kg,
-            m: l_m,
-            s: l_s,
-            a: l_a,
-            k: l_k,
-            mol: l_mol,
-        } = left {
-            if let Float {
-                value: r_value,
-                kg: r_kg,
-                m: r_m,
-                s: r_s,
-                a: r_a,
-                k: r_k,
-                mol: r_mol,
-            } = right {
+        match (left, right) {
+            (
+                Float {
+                    value: l_value,
+                    kg: l_kg,
+                    m: l_m,
+                    s: l_s,
+                    a: l_a,
+                    k: l_k,
+                    mol: l_mol,
+                    cd: l_cd,
+                    other: l_other,
+                },
+                Float {
+                    value: r_value,
+                    kg: r_kg,
+                    m: r_m,
+                    s: r_s,
+                    a: r_a,
+                    k: r_k,
+                    mol: r_mol,
+                    cd: r_cd,
+                    other: r_other,
+                },
+            ) => {
                 // Left & Right are numeric
                 Float {
                     value: self.oper_value(l_value, r_value),
@@ -62,33 +111,85 @@ impl BinaryOperation {
                     a: self.oper_unit("A", l_a, r_a),
                     k: self.oper_unit("K", l_k, r_k),
                     mol: self.oper_unit("mol", l_mol, r_mol),
+                    cd: self.oper_unit("cd", l_cd, r_cd),
+                    other: self.oper_dimensions(l_other, r_other),
                 }
+            },
+            (Formatted { value, .. }, r) => {
+                // Notation only applies to the displayed value
+                self.simplify(value, r)
+            },
+            (l, Formatted { value, .. }) => self.simplify(l, value),
+            (Ratio { value, .. }, r) => {
+                // Dimensionless units only apply to the displayed value
+                self.simplify(value, r)
+            },
+            (l, Ratio { value, .. }) => self.simplify(l, value),
+            (Vector (l), Vector (r)) => if l.len() == r.len() {
+                // Operate element-wise
+                Vector (l.iter()
+                    .zip(r.iter())
+                    .map(|(l, r)| self.simplify(l, r))
+                    .collect::<Vec<Expression>>()
+                )
             } else {
-                // Left is numeric, Right is not
-                BinOp {
-                    left: Box::new(left.to_owned()),
-                    oper: *self,
-                    right: Box::new(right.to_owned()),
-                }
-            }
-        } else {
-            BinOp {
+                Error::UnmatchedLengths (l.len(), r.len()).warn();
+                Null
+            },
+            (Vector (l), r) if r.is_numeric() => {
+                // Broadcast the right operand
+                Vector (l.iter()
+                    .map(|l| self.simplify(l, r))
+                    .collect::<Vec<Expression>>()
+                )
+            },
+            (l, Vector (r)) if l.is_numeric() => {
+                // Broadcast the left operand
+                Vector (r.iter()
+                    .map(|r| self.simplify(l, r))
+                    .collect::<Vec<Expression>>()
+                )
+            },
+            _ => BinOp {
                 left: Box::new(left.to_owned()),
                 oper: *self,
                 right: Box::new(right.to_owned()),
-            }
+            },
         }
     }
 
     /// Operate on two numbers.
-    pub fn oper_value(&self, left: &f64, right: &f64) -> f64 {
+    pub fn oper_value(&self, left: &Number, right: &Number) -> Number {
         use BinaryOperation::*;
 
         match self {
-            Add => left + right,
-            Sub => left - right,
-            Mul => left * right,
-            Div => left / right,
+            Add => left.to_owned() + right.to_owned(),
+            Sub => left.to_owned() - right.to_owned(),
+            Mul => left.to_owned() * right.to_owned(),
+            Div => left.to_owned() / right.to_owned(),
+        }
+    }
+
+    /// Operate on two sets of other dimensions.
+    pub fn oper_dimensions(&self, left: &Dimensions, right: &Dimensions) -> Dimensions {
+        use BinaryOperation::*;
+
+        match self {
+            Add | Sub => {
+                let difference = left.combine(right, -1.0);
+
+                if let Some ((dimension, _)) = difference.iter().next() {
+                    let power = |d: &Dimensions| d.iter()
+                        .find(|(n, _)| n == dimension)
+                        .map_or(0.0, |(_, p)| *p);
+
+                    Error::UnmatchedUnits (dimension, &power(left).to_string(), &power(right).to_string()).warn();
+                }
+
+                left.to_owned()
+            },
+            Mul => left.combine(right, 1.0),
+            Div => left.combine(right, -1.0),
         }
     }
 
@@ -103,8 +204,8 @@ impl BinaryOperation {
                 Error::UnmatchedUnits (unit, &left.to_string(), &right.to_string()).warn();
                 return 0.0;
             },
-            Mul => left + right,
-            Div => left - right,
+            Mul => round_power(left + right),
+            Div => round_power(left - right),
         }
     }
 }
@@ -0,0 +1,86 @@
+//! Defines evenly stepped vectors for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Error,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    MAX_POINTS,
+    unpack,
+    values_in,
+    with_unit,
+};
+
+/// Evaluates a vector of values from `start` up to (but excluding) `stop`,
+/// separated by `step`.  If no step is provided, the step is one unit.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    if arguments.len() != 2 && arguments.len() != 3 {
+        Error::InvalidArgument ("arange", "expected 2 or 3 numeric arguments").warn();
+        return Expression::Null;
+    }
+
+    let (start, unit) = match unpack("arange", &arguments[0]) {
+        Some ((v, u)) => (v[0], u),
+        None => return Expression::Null,
+    };
+
+    let stop = match values_in("arange", &arguments[1], &unit) {
+        Some (v) => v[0],
+        None => return Expression::Null,
+    };
+
+    let step = match arguments.get(2) {
+        Some (s) => match values_in("arange", s, &unit) {
+            Some (v) => v[0],
+            None => return Expression::Null,
+        },
+        None => 1.0,
+    };
+
+    if step == 0.0 || (stop - start) / step < 0.0 {
+        Error::InvalidArgument ("arange", "step does not move from start toward stop").warn();
+        return Expression::Null;
+    }
+
+    // Tolerate rounding error in the final step
+    let n = ((stop - start) / step - 1E-9).ceil().max(0.0);
+
+    if n > MAX_POINTS as f64 {
+        Error::InvalidArgument ("arange", &format!("expected at most {} points but found {}", MAX_POINTS, n)).warn();
+        return Expression::Null;
+    }
+
+    let n = n as usize;
+
+    Expression::Vector ((0..n)
+        .map(|i| with_unit(start + i as f64 * step, &unit))
+        .collect::<Vec<Expression>>()
+    )
+}
+
+/// Formats a stepped vector.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("arange", arguments, settings)
+}
+
+/// Converts a stepped vector to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("arange", arguments, settings)
+}
+
+/// Converts a stepped vector to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("arange", arguments, settings)
+}
+
+/// Converts a stepped vector to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("arange", arguments, settings)
+}
@@ -0,0 +1,54 @@
+//! Defines column access on tables for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Error,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+};
+
+/// Evaluates a column of a table by name (e.g. `column(data, "force")`).
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let (columns, name) = match arguments {
+        [Expression::Table (t), Expression::Text (n)] => (t, n),
+        _ => {
+            Error::InvalidArgument ("column", "expected a table and a column name").warn();
+            return Expression::Null;
+        },
+    };
+
+    match columns.iter().find(|(n, _)| n == name) {
+        Some ((_, c)) => c.to_owned(),
+        None => {
+            Error::InvalidArgument ("column", &format!("no column named {}", name)).warn();
+            Expression::Null
+        },
+    }
+}
+
+/// Formats a column access.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("column", arguments, settings)
+}
+
+/// Converts a column access to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("column", arguments, settings)
+}
+
+/// Converts a column access to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("column", arguments, settings)
+}
+
+/// Converts a column access to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("column", arguments, settings)
+}
@@ -0,0 +1,322 @@
+//! Defines the CSV data import function for the Carlo language.
+
+use std::{
+    fs,
+    path::Path,
+};
+
+use crate::{
+    BinaryOperation,
+    DisplaySettings,
+    Error,
+    Expression,
+    Number,
+    parser::unit_value,
+    UnitTable,
+};
+
+use super::{
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+};
+
+/// Imports a CSV file as a table of unit-tagged vectors.
+pub fn eval(arguments: &[Expression], table: &UnitTable) -> Expression {
+    match load(arguments, table) {
+        Ok (t) => t,
+        Err (e) => {
+            e.report();
+            Expression::Null
+        },
+    }
+}
+
+/// Imports a CSV file as a table of unit-tagged vectors, returning an error
+/// if the file cannot be read or has a malformed row.
+pub fn load(arguments: &[Expression], table: &UnitTable) -> Result<Expression, Error<String>> {
+    let mut path = None;
+    let mut units = None;
+
+    for argument in arguments {
+        match argument {
+            Expression::Text (p) => path = Some (p.to_owned()),
+            Expression::NamedArgument {
+                name,
+                value,
+            } if name == "units" => units = match column_units(value, table) {
+                Some (u) => Some (u),
+                None => return Ok (Expression::Null),
+            },
+            a => {
+                Error::InvalidArgument ("csv", &a.to_string()).warn();
+                return Ok (Expression::Null);
+            },
+        }
+    }
+
+    let path = match path {
+        Some (p) => p,
+        None => {
+            Error::InvalidArgument ("csv", "no file provided").warn();
+            return Ok (Expression::Null);
+        },
+    };
+
+    let contents = match fs::read_to_string(&path) {
+        Ok (c) => c,
+        Err (_) => return Err (Error::CouldNotReadFile (path)),
+    };
+
+    read_table(&contents, units, table)
+        .map_err(|(line, reason)| Error::MalformedRow (path, line.to_string(), reason))
+}
+
+/// Finds the files of a CSV import relative to a directory
+/// (that of the source file).
+pub fn locate(arguments: &[Expression], directory: Option<&Path>) -> Vec<Expression> {
+    arguments.iter()
+        .map(|a| match (a, directory) {
+            (Expression::Text (p), Some (d)) => Expression::Text (d.join(p).display().to_string()),
+            _ => a.to_owned(),
+        })
+        .collect::<Vec<Expression>>()
+}
+
+/// Formats a CSV import.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("csv", arguments, settings)
+}
+
+/// Converts a CSV import to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("csv", arguments, settings)
+}
+
+/// Converts a CSV import to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("csv", arguments, settings)
+}
+
+/// Converts a CSV import to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("csv", arguments, settings)
+}
+
+/// Reads the units provided to the `units` argument.
+fn column_units(value: &Expression, table: &UnitTable) -> Option<Vec<Expression>> {
+    let elements = match value {
+        Expression::Vector (v) => v,
+        e => {
+            Error::InvalidArgument ("csv", &format!("units: {}", e)).warn();
+            return None;
+        },
+    };
+
+    let mut units = Vec::new();
+
+    for element in elements {
+        let unit = match element {
+            Expression::Identifier (u) => match header_unit(u, table) {
+                Some (u) => u,
+                None => {
+                    Error::UnrecognizedUnit (u).warn();
+                    return None;
+                },
+            },
+            f @ Expression::Float { .. } => f.to_owned(),
+            e => {
+                Error::InvalidArgument ("csv", &format!("units: {}", e)).warn();
+                return None;
+            },
+        };
+
+        units.push(unit);
+    }
+
+    Some (units)
+}
+
+/// Parses a (possibly compound) unit such as `kN`, `m/s^2` or `m s_2`.
+fn header_unit(input: &str, table: &UnitTable) -> Option<Expression> {
+    let mut unit = dimensionless();
+
+    for part in input.split_whitespace() {
+        unit = BinaryOperation::Mul.simplify(&unit, &unit_value(part, table)?);
+    }
+
+    Some (unit)
+}
+
+/// Gets the unit of a dimensionless column.
+fn dimensionless() -> Expression {
+    Expression::dimensionless(Number::exact(1.0))
+}
+
+/// Splits a line of a CSV file into fields, respecting quotation marks.
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+
+    let mut chars = line.chars().peekable();
+    while let Some (c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some (&'"') => {
+                chars.next();
+                field.push('"');
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields.iter()
+        .map(|f| f.trim().to_string())
+        .collect::<Vec<String>>()
+}
+
+/// Splits a header field such as `force [kN]` into a column name and a unit.
+fn split_header(field: &str) -> (String, Option<String>) {
+    let (name, unit) = match (field.find('['), field.rfind(']')) {
+        (Some (i), Some (j)) if i < j => (&field[..i], Some (field[i + 1..j].trim().to_string())),
+        _ => (field, None),
+    };
+
+    // Column names must be valid identifiers
+    let name = name.trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+
+    (name, unit)
+}
+
+/// Reads the contents of a CSV file into a table.
+/// Errors are returned as a line number and a reason.
+fn read_table(contents: &str, units: Option<Vec<Expression>>, table: &UnitTable) -> Result<Expression, (usize, String)> {
+    let mut names: Vec<String> = Vec::new();
+    let mut header_units: Vec<Option<String>> = Vec::new();
+    let mut columns: Vec<Vec<Number>> = Vec::new();
+    let mut width = None;
+    let mut header_line = 1;
+
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = split_fields(line);
+
+        // The first row is a header if it contains non-numeric fields
+        if width.is_none() {
+            width = Some (fields.len());
+            header_line = line_number;
+            columns = vec![Vec::new(); fields.len()];
+
+            if fields.iter().any(|f| str::parse::<f64>(f).is_err()) {
+                for field in &fields {
+                    let (name, unit) = split_header(field);
+                    names.push(name);
+                    header_units.push(unit);
+                }
+                continue;
+            }
+        }
+
+        if Some (fields.len()) != width {
+            return Err ((line_number, format!(
+                "expected {} fields but found {}",
+                width.unwrap_or(0),
+                fields.len(),
+            )));
+        }
+
+        for (j, field) in fields.iter().enumerate() {
+            match Number::parse(field) {
+                Some (n) => columns[j].push(n),
+                None => return Err ((line_number, format!(
+                    "could not parse field {} (\"{}\") as a number",
+                    j + 1,
+                    field,
+                ))),
+            }
+        }
+    }
+
+    let width = width.unwrap_or(0);
+
+    // Name columns that have no header
+    if names.is_empty() {
+        names = (1..=width).map(|j| format!("col{}", j)).collect::<Vec<String>>();
+        header_units = vec![None; width];
+    }
+
+    // Determine the unit of each column
+    let units = match units {
+        Some (u) => if u.len() == width {
+            u
+        } else {
+            return Err ((header_line, format!("expected {} units but found {} columns", u.len(), width)));
+        },
+        None => {
+            let mut units = Vec::new();
+            for unit in &header_units {
+                units.push(match unit {
+                    Some (u) => match header_unit(u, table) {
+                        Some (u) => u,
+                        None => return Err ((header_line, format!("did not recognize unit {}", u))),
+                    },
+                    None => dimensionless(),
+                });
+            }
+            units
+        },
+    };
+
+    let columns = names.into_iter()
+        .zip(columns)
+        .zip(units)
+        .map(|((name, column), unit)| (
+            name,
+            Expression::Vector (column.iter()
+                .map(|value| BinaryOperation::Mul.simplify(&Expression::dimensionless(value.to_owned()), &unit))
+                .collect::<Vec<Expression>>()
+            ),
+        ))
+        .collect::<Vec<(String, Expression)>>();
+
+    Ok (Expression::Table (columns))
+}
+
+#[test]
+fn test_read_table() {
+    let contents = "time [s], force [kN]\n0.0, 1.5\n0.5, 2.0\n";
+
+    let table = match read_table(contents, None, &UnitTable::default()) {
+        Ok (Expression::Table (t)) => t,
+        _ => panic!("could not read table"),
+    };
+
+    assert_eq!(table.len(), 2);
+    assert_eq!(table[1].0, "force");
+
+    match &table[1].1 {
+        Expression::Vector (v) => match &v[1] {
+            Expression::Float { value, kg, m, s, .. } => {
+                assert_eq!(value.to_f64(), 2000.0);
+                assert_eq!((*kg, *m, *s), (1.0, 1.0, -2.0));
+            },
+            _ => panic!("expected a float"),
+        },
+        _ => panic!("expected a vector"),
+    }
+
+    let malformed = read_table("time, force\n0.0, 1.5\n0.5\n", None, &UnitTable::default());
+    assert!(matches!(malformed, Err ((3, _))));
+}
@@ -0,0 +1,76 @@
+//! Defines the display function for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Error,
+    Expression,
+    Notation,
+    UnitTable,
+};
+
+use super::{
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    value_of,
+};
+
+/// Evaluates a value with its own notation (e.g. `display(x, sig: 3)`).
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let notation = match notation(arguments) {
+        Some (n) => n,
+        None => {
+            Error::InvalidArgument ("display", "expected a value and one of `fixed`, `sig`, `sci` or `eng` with a number of digits").warn();
+            return Expression::Null;
+        },
+    };
+
+    Expression::Formatted {
+        value: Box::new(arguments[0].to_owned()),
+        notation,
+    }
+}
+
+/// Gets the notation requested by the arguments of a display function.
+fn notation(arguments: &[Expression]) -> Option<Notation> {
+    match arguments {
+        [_, Expression::NamedArgument { name, value }] => {
+            let digits = value_of(value).filter(|d| value.is_dimensionless() && *d >= 0.0 && d.fract() == 0.0)?;
+            Notation::parse(name, Some (&digits.to_string()))
+        },
+        _ => None,
+    }
+}
+
+/// Formats a value with its own notation.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match notation(arguments) {
+        Some (n) => arguments[0].format(&settings.with_notation(n)),
+        None => format_call("display", arguments, settings),
+    }
+}
+
+/// Converts a value with its own notation to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match notation(arguments) {
+        Some (n) => arguments[0].latex(true, &settings.with_notation(n)),
+        None => latex_call("display", arguments, settings),
+    }
+}
+
+/// Converts a value with its own notation to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match notation(arguments) {
+        Some (n) => arguments[0].mathml(true, &settings.with_notation(n)),
+        None => mathml_call("display", arguments, settings),
+    }
+}
+
+/// Converts a value with its own notation to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match notation(arguments) {
+        Some (n) => arguments[0].typst(true, &settings.with_notation(n)),
+        None => typst_call("display", arguments, settings),
+    }
+}
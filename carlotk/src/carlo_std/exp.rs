@@ -0,0 +1,69 @@
+//! Defines the exponential function for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Expression,
+    Number,
+    UnitTable,
+};
+
+use super::{
+    dimensionless_value,
+    expect_arguments,
+    format_call,
+    latex_call,
+    mathml_call,
+    typst_call,
+};
+
+/// Evaluates the exponential function of a dimensionless number.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("exp", arguments, 1) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    match dimensionless_value("exp", &arguments[0]) {
+        Some (x) => Expression::dimensionless(Number::from(x.exp())),
+        None => Expression::Null,
+    }
+}
+
+/// Formats the exponential function.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("exp", arguments, settings)
+}
+
+/// Converts the exponential function to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match arguments {
+        [x] => format!("\\exp\\left({}\\right)", x.latex(true, settings)),
+        _ => latex_call("exp", arguments, settings),
+    }
+}
+
+/// Converts the exponential function to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match arguments {
+        [x] => format!("<mi>exp</mi><mo>&#x2061;</mo><mrow><mo>(</mo>{}<mo>)</mo></mrow>", x.mathml(true, settings)),
+        _ => mathml_call("exp", arguments, settings),
+    }
+}
+
+/// Converts the exponential function to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match arguments {
+        [x] => format!("exp({})", x.typst(true, settings)),
+        _ => typst_call("exp", arguments, settings),
+    }
+}
+
+#[test]
+fn test_exp() {
+    let parser = crate::Parser::new(false);
+    let mut env = crate::Environment::new();
+
+    // Arguments must be dimensionless, including dimensionless units
+    let output = env.evaluate(&parser.parse("exp(0)\nlog10(1000)\nln(1 m)\nexp(2 mm/m)\n5 % * 200 N\n"));
+    assert_eq!(output, "1.0000\n3.0000\nNull\n1.0020\n10.0000 N\n");
+}
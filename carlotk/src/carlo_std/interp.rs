@@ -0,0 +1,113 @@
+//! Defines linear interpolation for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Error,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    expect_arguments,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    unpack,
+    values_in,
+    with_unit,
+};
+
+/// Evaluates `interp(x, xs, ys)`, linearly interpolating the tabulated
+/// values `ys` at `x`.  If `x` is a vector, each element is interpolated.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("interp", arguments, 3) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    let (xs, x_unit) = match unpack("interp", &arguments[1]) {
+        Some (v) => v,
+        None => return Expression::Null,
+    };
+
+    let (ys, y_unit) = match unpack("interp", &arguments[2]) {
+        Some (v) => v,
+        None => return Expression::Null,
+    };
+
+    if xs.len() != ys.len() {
+        Error::UnmatchedLengths (xs.len(), ys.len()).warn();
+        return Expression::Null;
+    }
+
+    let x = match values_in("interp", &arguments[0], &x_unit) {
+        Some (v) => v,
+        None => return Expression::Null,
+    };
+
+    // Sort the tabulated points by x
+    let mut points = xs.into_iter().zip(ys).collect::<Vec<(f64, f64)>>();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut results = Vec::new();
+    for x in x {
+        match interp(x, &points) {
+            Some (y) => results.push(with_unit(y, &y_unit)),
+            None => {
+                Error::InvalidArgument ("interp", &format!(
+                    "{} is outside of the tabulated range",
+                    with_unit(x, &x_unit),
+                )).warn();
+                return Expression::Null;
+            },
+        }
+    }
+
+    match &arguments[0] {
+        Expression::Vector (_) => Expression::Vector (results),
+        _ => results.remove(0),
+    }
+}
+
+/// Interpolates linearly between sorted points, returning `None`
+/// if `x` is outside of the range of the points.
+pub fn interp(x: f64, points: &[(f64, f64)]) -> Option<f64> {
+    for pair in points.windows(2) {
+        let (x0, y0) = pair[0];
+        let (x1, y1) = pair[1];
+
+        if x0 <= x && x <= x1 {
+            if x1 == x0 {
+                return Some (y0);
+            }
+
+            return Some (y0 + (x - x0) * (y1 - y0) / (x1 - x0));
+        }
+    }
+
+    match points {
+        [(x0, y0)] if *x0 == x => Some (*y0),
+        _ => None,
+    }
+}
+
+/// Formats a linear interpolation.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("interp", arguments, settings)
+}
+
+/// Converts a linear interpolation to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("interp", arguments, settings)
+}
+
+/// Converts a linear interpolation to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("interp", arguments, settings)
+}
+
+/// Converts a linear interpolation to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("interp", arguments, settings)
+}
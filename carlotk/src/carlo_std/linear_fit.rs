@@ -0,0 +1,103 @@
+//! Defines least-squares linear regression for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    BinaryOperation,
+    Error,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    expect_arguments,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    mean::mean,
+    unpack,
+    with_unit,
+};
+
+/// Evaluates the least-squares line `y = slope x + intercept` through
+/// two vectors, returning a table with columns `slope` and `intercept`.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("linear_fit", arguments, 2) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    let (xs, x_unit) = match unpack("linear_fit", &arguments[0]) {
+        Some (v) => v,
+        None => return Expression::Null,
+    };
+
+    let (ys, y_unit) = match unpack("linear_fit", &arguments[1]) {
+        Some (v) => v,
+        None => return Expression::Null,
+    };
+
+    if xs.len() != ys.len() {
+        Error::UnmatchedLengths (xs.len(), ys.len()).warn();
+        return Expression::Null;
+    }
+
+    let (slope, intercept) = match linear_fit(&xs, &ys) {
+        Some (f) => f,
+        None => {
+            Error::InvalidArgument ("linear_fit", "expected at least two distinct x values").warn();
+            return Expression::Null;
+        },
+    };
+
+    Expression::Table (vec![
+        ("slope".to_string(), with_unit(slope, &BinaryOperation::Div.simplify(&y_unit, &x_unit))),
+        ("intercept".to_string(), with_unit(intercept, &y_unit)),
+    ])
+}
+
+/// Computes the slope and intercept of the least-squares line through a set of points.
+pub fn linear_fit(xs: &[f64], ys: &[f64]) -> Option<(f64, f64)> {
+    let x_mean = mean(xs);
+    let y_mean = mean(ys);
+
+    let sxx = xs.iter().map(|x| (x - x_mean).powi(2)).sum::<f64>();
+    let sxy = xs.iter().zip(ys).map(|(x, y)| (x - x_mean) * (y - y_mean)).sum::<f64>();
+
+    if sxx == 0.0 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+
+    Some ((slope, y_mean - slope * x_mean))
+}
+
+/// Formats a linear regression.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("linear_fit", arguments, settings)
+}
+
+/// Converts a linear regression to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("linear_fit", arguments, settings)
+}
+
+/// Converts a linear regression to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("linear_fit", arguments, settings)
+}
+
+/// Converts a linear regression to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("linear_fit", arguments, settings)
+}
+
+#[test]
+fn test_linear_fit() {
+    let xs = [0.0, 1.0, 2.0, 3.0];
+    let ys = [1.0, 3.0, 5.0, 7.0];
+
+    assert_eq!(linear_fit(&xs, &ys), Some ((2.0, 1.0)));
+    assert_eq!(linear_fit(&[1.0, 1.0], &[0.0, 1.0]), None);
+}
@@ -0,0 +1,97 @@
+//! Defines linearly spaced vectors for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Error,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    expect_arguments,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    MAX_POINTS,
+    unpack,
+    value_of,
+    values_in,
+    with_unit,
+};
+
+/// Evaluates a vector of `n` evenly spaced values from `start` to `stop` (inclusive).
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("linspace", arguments, 3) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    let (start, unit) = match unpack("linspace", &arguments[0]) {
+        Some ((v, u)) => (v[0], u),
+        None => return Expression::Null,
+    };
+
+    let stop = match values_in("linspace", &arguments[1], &unit) {
+        Some (v) => v[0],
+        None => return Expression::Null,
+    };
+
+    let n = match value_of(&arguments[2]) {
+        Some (n) if arguments[2].is_dimensionless() && n >= 2.0 && n.fract() == 0.0 => n,
+        _ => {
+            Error::InvalidArgument ("linspace", "expected an integer number of points of at least 2").warn();
+            return Expression::Null;
+        },
+    };
+
+    if n > MAX_POINTS as f64 {
+        Error::InvalidArgument ("linspace", &format!("expected at most {} points but found {}", MAX_POINTS, n)).warn();
+        return Expression::Null;
+    }
+
+    let n = n as usize;
+
+    let step = (stop - start) / (n - 1) as f64;
+
+    Expression::Vector ((0..n)
+        .map(|i| with_unit(start + i as f64 * step, &unit))
+        .collect::<Vec<Expression>>()
+    )
+}
+
+/// Formats a linearly spaced vector.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("linspace", arguments, settings)
+}
+
+/// Converts a linearly spaced vector to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("linspace", arguments, settings)
+}
+
+/// Converts a linearly spaced vector to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("linspace", arguments, settings)
+}
+
+/// Converts a linearly spaced vector to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("linspace", arguments, settings)
+}
+
+#[test]
+fn test_linspace() {
+    let n = |expr: &Expression| match expr {
+        Expression::Vector (v) => v.len(),
+        _ => 0,
+    };
+
+    let count = |n: f64| Expression::dimensionless(crate::Number::from(n));
+    let (start, stop) = (count(0.0), count(1.0));
+
+    assert_eq!(n(&eval(&[start.to_owned(), stop.to_owned(), count(5.0)], &UnitTable::default())), 5);
+
+    // Too many points is an error rather than an allocation failure
+    assert!(matches!(eval(&[start, stop, count(1E12)], &UnitTable::default()), Expression::Null));
+}
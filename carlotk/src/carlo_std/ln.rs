@@ -0,0 +1,59 @@
+//! Defines the natural logarithm for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Expression,
+    Number,
+    UnitTable,
+};
+
+use super::{
+    dimensionless_value,
+    expect_arguments,
+    format_call,
+    latex_call,
+    mathml_call,
+    typst_call,
+};
+
+/// Evaluates the natural logarithm of a dimensionless number.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("ln", arguments, 1) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    match dimensionless_value("ln", &arguments[0]) {
+        Some (x) => Expression::dimensionless(Number::from(x.ln())),
+        None => Expression::Null,
+    }
+}
+
+/// Formats the natural logarithm.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("ln", arguments, settings)
+}
+
+/// Converts the natural logarithm to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match arguments {
+        [x] => format!("\\ln\\left({}\\right)", x.latex(true, settings)),
+        _ => latex_call("ln", arguments, settings),
+    }
+}
+
+/// Converts the natural logarithm to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match arguments {
+        [x] => format!("<mi>ln</mi><mo>&#x2061;</mo><mrow><mo>(</mo>{}<mo>)</mo></mrow>", x.mathml(true, settings)),
+        _ => mathml_call("ln", arguments, settings),
+    }
+}
+
+/// Converts the natural logarithm to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match arguments {
+        [x] => format!("ln({})", x.typst(true, settings)),
+        _ => typst_call("ln", arguments, settings),
+    }
+}
@@ -0,0 +1,62 @@
+//! Defines the base-10 logarithm for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Expression,
+    Number,
+    UnitTable,
+};
+
+use super::{
+    dimensionless_value,
+    expect_arguments,
+    format_call,
+    latex_call,
+    mathml_call,
+    typst_call,
+};
+
+/// Evaluates the base-10 logarithm of a dimensionless number.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("log10", arguments, 1) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    match dimensionless_value("log10", &arguments[0]) {
+        Some (x) => Expression::dimensionless(Number::from(x.log10())),
+        None => Expression::Null,
+    }
+}
+
+/// Formats the base-10 logarithm.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("log10", arguments, settings)
+}
+
+/// Converts the base-10 logarithm to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match arguments {
+        [x] => format!("\\log_{{10}}\\left({}\\right)", x.latex(true, settings)),
+        _ => latex_call("log10", arguments, settings),
+    }
+}
+
+/// Converts the base-10 logarithm to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match arguments {
+        [x] => format!(
+            "<msub><mi>log</mi><mn>10</mn></msub><mo>&#x2061;</mo><mrow><mo>(</mo>{}<mo>)</mo></mrow>",
+            x.mathml(true, settings),
+        ),
+        _ => mathml_call("log10", arguments, settings),
+    }
+}
+
+/// Converts the base-10 logarithm to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match arguments {
+        [x] => format!("log_10({})", x.typst(true, settings)),
+        _ => typst_call("log10", arguments, settings),
+    }
+}
@@ -0,0 +1,65 @@
+//! Defines the arithmetic mean for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    BinaryOperation,
+    Expression,
+    Number,
+    UnitTable,
+};
+
+use super::{
+    elements,
+    expect_arguments,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    unpack,
+};
+
+/// Evaluates the arithmetic mean of a vector.
+/// The mean is computed exactly if the elements are exact.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("mean", arguments, 1) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    if unpack("mean", &arguments[0]).is_none() {
+        return Expression::Null;
+    }
+
+    let elements = elements(&arguments[0]);
+    let n = Expression::dimensionless(Number::exact(elements.len() as f64));
+
+    let sum = elements[1..].iter()
+        .fold(elements[0].to_owned(), |sum, e| BinaryOperation::Add.simplify(&sum, e));
+
+    BinaryOperation::Div.simplify(&sum, &n)
+}
+
+/// Computes the arithmetic mean of a list of values.
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Formats an arithmetic mean.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("mean", arguments, settings)
+}
+
+/// Converts an arithmetic mean to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("mean", arguments, settings)
+}
+
+/// Converts an arithmetic mean to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("mean", arguments, settings)
+}
+
+/// Converts an arithmetic mean to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("mean", arguments, settings)
+}
@@ -0,0 +1,51 @@
+//! Defines the median for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    expect_arguments,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    percentile::percentile,
+    unpack,
+    with_unit,
+};
+
+/// Evaluates the median of a vector.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("median", arguments, 1) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    match unpack("median", &arguments[0]) {
+        Some ((values, unit)) => with_unit(percentile(&values, 50.0), &unit),
+        None => Expression::Null,
+    }
+}
+
+/// Formats a median.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("median", arguments, settings)
+}
+
+/// Converts a median to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("median", arguments, settings)
+}
+
+/// Converts a median to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("median", arguments, settings)
+}
+
+/// Converts a median to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("median", arguments, settings)
+}
@@ -0,0 +1,56 @@
+//! Defines the minimum for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    elements,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    unpack,
+};
+
+/// Evaluates the minimum of a vector or of a list of arguments.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let list = match arguments.len() {
+        1 => arguments[0].to_owned(),
+        _ => Expression::Vector (arguments.to_owned()),
+    };
+
+    let values = match unpack("min", &list) {
+        Some ((v, _)) => v,
+        None => return Expression::Null,
+    };
+
+    // Select the element itself to preserve exact values
+    let i = (0..values.len())
+        .min_by(|i, j| values[*i].total_cmp(&values[*j]))
+        .unwrap_or(0);
+
+    elements(&list).swap_remove(i)
+}
+
+/// Formats a minimum.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("min", arguments, settings)
+}
+
+/// Converts a minimum to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("min", arguments, settings)
+}
+
+/// Converts a minimum to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("min", arguments, settings)
+}
+
+/// Converts a minimum to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("min", arguments, settings)
+}
@@ -1,7 +1,264 @@
 //! Contains the Carlo standard library.
 
-use crate::construct_std;
+use crate::{
+    construct_std,
+    BinaryOperation,
+    Error,
+    escape_html,
+    typst::typst_string,
+    Number,
+    dimension::describe,
+};
+
+/// Standard library functions that receive their arguments as written,
+/// without resolving identifiers.
+pub const UNEVALUATED: [&str; 2] = [
+    "csv",
+    "unit",
+];
+
+/// Standard library functions that return tables, which are evaluated
+/// once when they are assigned to a variable.
+pub const TABLES: [&str; 2] = [
+    "csv",
+    "linear_fit",
+];
+
+/// Largest number of points in a generated vector (e.g. by `linspace`).
+const MAX_POINTS: usize = 10_000_000;
+
+/// Standard library functions whose arguments must be dimensionless.
+pub const DIMENSIONLESS_ARGUMENTS: [&str; 3] = [
+    "exp",
+    "ln",
+    "log10",
+];
+
+/// Standard library functions whose arguments must share dimensions, each with the
+/// positions of those arguments (or `None` for all of their arguments).
+pub const SAME_DIMENSIONS: [(&str, Option<&[usize]>); 6] = [
+    ("min", None),
+    ("max", None),
+    ("range", None),
+    ("linspace", Some (&[0, 1])),
+    ("arange", None),
+    ("interp", Some (&[0, 1])),
+];
 
 construct_std!{
     func sqrt
-}
\ No newline at end of file
+    func exp
+    func ln
+    func log10
+    func csv
+    func column
+    func mean
+    func median
+    func variance
+    func stdev
+    func percentile
+    func min
+    func max
+    func range
+    func linspace
+    func arange
+    func linear_fit
+    func interp
+    func display
+    func unit
+}
+
+pub(crate) use csv::{
+    load as read_csv,
+    locate as locate_csv,
+};
+
+/// Infers the dimensions of a call to a standard library function from the
+/// dimensions of its arguments (each a float of value 1, or `None` if unknown).
+/// 
+/// Returns `None` if the dimensions of the result cannot be inferred.
+pub fn dimensions(name: &str, arguments: &[Option<Expression>], units: &UnitTable) -> Option<Expression> {
+    let first = arguments.first()?.as_ref()?;
+
+    match name {
+        "sqrt" => unit_of(&sqrt::eval(&[first.to_owned()], units)),
+        "mean" | "median" | "stdev" | "percentile" | "min" | "max" | "range" | "linspace" | "arange" | "display" => Some (first.to_owned()),
+        "variance" => Some (BinaryOperation::Mul.simplify(first, first)),
+        "interp" => arguments.get(2)?.to_owned(),
+        "exp" | "ln" | "log10" => Some (Expression::dimensionless(Number::exact(1.0))),
+        _ => None,
+    }
+}
+
+/// Formats a generic function call.
+fn format_call(name: &str, arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format!(
+        "{}({})",
+        name,
+        arguments.iter()
+            .map(|a| a.format(settings))
+            .collect::<Vec<String>>()
+            .join(", "),
+    )
+}
+
+/// Converts a generic function call to LaTeX.
+fn latex_call(name: &str, arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format!(
+        "\\mathrm{{{}}}\\left({}\\right)",
+        name.replace('_', "\\_"),
+        arguments.iter()
+            .map(|a| a.latex(true, settings))
+            .collect::<Vec<String>>()
+            .join(", "),
+    )
+}
+
+/// Converts a generic function call to MathML.
+fn mathml_call(name: &str, arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format!(
+        "<mrow><mi mathvariant=\"normal\">{}</mi><mo>&#x2061;</mo><mrow><mo>(</mo>{}<mo>)</mo></mrow></mrow>",
+        escape_html(name),
+        arguments.iter()
+            .map(|a| a.mathml(true, settings))
+            .collect::<Vec<String>>()
+            .join("<mo>,</mo>"),
+    )
+}
+
+/// Converts a generic function call to Typst.
+fn typst_call(name: &str, arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format!(
+        "op({}) ({})",
+        typst_string(name),
+        arguments.iter()
+            .map(|a| a.typst(true, settings))
+            .collect::<Vec<String>>()
+            .join(", "),
+    )
+}
+
+/// Gets the unit of a numeric expression as a float of value 1.
+pub(crate) fn unit_of(expr: &Expression) -> Option<Expression> {
+    match expr {
+        Expression::Formatted { value, .. } | Expression::Ratio { value, .. } => unit_of(value),
+        Expression::Float {
+            value: _,
+            kg,
+            m,
+            s,
+            a,
+            k,
+            mol,
+            cd,
+            other,
+        } => Some (Expression::Float {
+            value: Number::from(1.0),
+            kg: *kg,
+            m: *m,
+            s: *s,
+            a: *a,
+            k: *k,
+            mol: *mol,
+            cd: *cd,
+            other: other.to_owned(),
+        }),
+        _ => None,
+    }
+}
+
+/// Gets the value of a numeric expression.
+fn value_of(expr: &Expression) -> Option<f64> {
+    match expr {
+        Expression::Float { value, .. } => Some (value.to_f64()),
+        Expression::Formatted { value, .. } | Expression::Ratio { value, .. } => value_of(value),
+        _ => None,
+    }
+}
+
+/// Constructs a numeric expression from a value and a unit.
+fn with_unit(value: f64, unit: &Expression) -> Expression {
+    BinaryOperation::Mul.simplify(&Expression::dimensionless(Number::from(value)), unit)
+}
+
+/// Gets the elements of a vector (or a scalar as a single element).
+fn elements(expr: &Expression) -> Vec<Expression> {
+    match expr {
+        Expression::Vector (v) => v.to_owned(),
+        e => vec![e.to_owned()],
+    }
+}
+
+/// Gets the values of a numeric expression in a given unit,
+/// warning the user if the units do not match.
+fn values_in(name: &str, expr: &Expression, unit: &Expression) -> Option<Vec<f64>> {
+    let mut values = Vec::new();
+
+    for element in &elements(expr) {
+        let (value, u) = match (value_of(element), unit_of(element)) {
+            (Some (v), Some (u)) => (v, u),
+            _ => {
+                Error::InvalidArgument (name, &element.to_string()).warn();
+                return None;
+            },
+        };
+
+        // Dividing by the expected unit must leave a dimensionless number
+        if !BinaryOperation::Div.simplify(&u, unit).is_dimensionless() {
+            Error::InvalidArgument (name, &format!("expected units of {} but found {}", unit, element)).warn();
+            return None;
+        }
+
+        values.push(value);
+    }
+
+    Some (values)
+}
+
+/// Gets the value of a dimensionless argument, warning the user
+/// (and naming its dimensions) if it has dimensions.
+fn dimensionless_value(name: &str, expr: &Expression) -> Option<f64> {
+    match unit_of(expr) {
+        Some (u) if u.is_dimensionless() => value_of(expr),
+        Some (_) => {
+            Error::NotDimensionless (name, &describe(expr)).warn();
+            None
+        },
+        None => {
+            Error::InvalidArgument (name, "expected a numeric argument").warn();
+            None
+        },
+    }
+}
+
+/// Splits a non-empty numeric vector into its values and its unit.
+fn unpack(name: &str, expr: &Expression) -> Option<(Vec<f64>, Expression)> {
+    let first = match expr {
+        Expression::Vector (v) => v.first(),
+        e => Some (e),
+    };
+
+    let unit = match first.and_then(unit_of) {
+        Some (u) => u,
+        None => {
+            Error::InvalidArgument (name, &format!("expected a non-empty numeric vector but found {}", expr)).warn();
+            return None;
+        },
+    };
+
+    values_in(name, expr, &unit).map(|v| (v, unit))
+}
+
+/// Gets exactly `n` arguments, warning the user otherwise.
+fn expect_arguments<'a>(name: &str, arguments: &'a [Expression], n: usize) -> Option<&'a [Expression]> {
+    if arguments.len() == n && arguments.iter().all(|a| a.is_numeric()) {
+        Some (arguments)
+    } else {
+        Error::InvalidArgument (name, &format!(
+            "expected {} numeric argument{}",
+            n,
+            if n == 1 { "" } else { "s" },
+        )).warn();
+        None
+    }
+}
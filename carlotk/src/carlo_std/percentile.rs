@@ -0,0 +1,84 @@
+//! Defines percentiles for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Error,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    expect_arguments,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    unpack,
+    value_of,
+    with_unit,
+};
+
+/// Evaluates a percentile (between 0 and 100) of a vector.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("percentile", arguments, 2) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    let (values, unit) = match unpack("percentile", &arguments[0]) {
+        Some (v) => v,
+        None => return Expression::Null,
+    };
+
+    let p = match value_of(&arguments[1]) {
+        Some (p) if arguments[1].is_dimensionless() && (0.0..=100.0).contains(&p) => p,
+        _ => {
+            Error::InvalidArgument ("percentile", "expected a percentile between 0 and 100").warn();
+            return Expression::Null;
+        },
+    };
+
+    with_unit(percentile(&values, p), &unit)
+}
+
+/// Computes a percentile of a list of values,
+/// interpolating linearly between the closest ranks.
+pub fn percentile(values: &[f64], p: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+
+    sorted[lower] + (rank - lower as f64) * (sorted[upper] - sorted[lower])
+}
+
+/// Formats a percentile.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("percentile", arguments, settings)
+}
+
+/// Converts a percentile to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("percentile", arguments, settings)
+}
+
+/// Converts a percentile to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("percentile", arguments, settings)
+}
+
+/// Converts a percentile to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("percentile", arguments, settings)
+}
+
+#[test]
+fn test_percentile() {
+    let values = [4.0, 1.0, 3.0, 2.0];
+
+    assert_eq!(percentile(&values, 0.0), 1.0);
+    assert_eq!(percentile(&values, 50.0), 2.5);
+    assert_eq!(percentile(&values, 100.0), 4.0);
+}
@@ -0,0 +1,71 @@
+//! Defines the range (maximum minus minimum) for the Carlo language.
+
+use crate::{
+    BinaryOperation,
+    DisplaySettings,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    elements,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    unpack,
+};
+
+/// Evaluates the range (maximum minus minimum) of a vector or of a list of arguments.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let list = match arguments.len() {
+        1 => arguments[0].to_owned(),
+        _ => Expression::Vector (arguments.to_owned()),
+    };
+
+    let values = match unpack("range", &list) {
+        Some ((v, _)) => v,
+        None => return Expression::Null,
+    };
+
+    // Subtract the elements themselves to preserve exact values
+    let max = (0..values.len())
+        .max_by(|i, j| values[*i].total_cmp(&values[*j]))
+        .unwrap_or(0);
+    let min = (0..values.len())
+        .min_by(|i, j| values[*i].total_cmp(&values[*j]))
+        .unwrap_or(0);
+
+    let elements = elements(&list);
+
+    BinaryOperation::Sub.simplify(&elements[max], &elements[min])
+}
+
+/// Formats a range.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("range", arguments, settings)
+}
+
+/// Converts a range to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("range", arguments, settings)
+}
+
+/// Converts a range to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("range", arguments, settings)
+}
+
+/// Converts a range to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("range", arguments, settings)
+}
+
+#[test]
+fn test_range() {
+    let parser = crate::Parser::new(false);
+    let mut env = crate::Environment::new();
+
+    let output = env.evaluate(&parser.parse("range([3 m, 1 m, 4 m])\narange(0 m, 3 m, 1 m)\n"));
+    assert_eq!(output, "3.0000 m\n[0.0000 m, 1.0000 m, 2.0000 m]\n");
+}
@@ -1,13 +1,22 @@
 //! Defines the square root function for the Carlo language.
 
-use crate::Expression;
+use crate::{
+    DisplaySettings,
+    Expression,
+    UnitTable,
+};
 
 /// Evaluates the square root.
-pub fn eval(arguments: &Vec<Expression>) -> Expression {
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
     if arguments.len() != 1 {
         return Expression::Null;
     }
 
+    // Notations and dimensionless units only apply to the displayed value
+    if let Expression::Formatted { value, .. } | Expression::Ratio { value, .. } = &arguments[0] {
+        return eval(&[*value.to_owned()], _units);
+    }
+
     if let Expression::Float {
         value,
         kg,
@@ -16,7 +25,9 @@ pub fn eval(arguments: &Vec<Expression>) -> Expression {
         a,
         k,
         mol,
-    } = arguments[0] {
+        cd,
+        other,
+    } = &arguments[0] {
         return Expression::Float {
             value: value.sqrt(),
             kg: kg * 0.5,
@@ -25,6 +36,8 @@ pub fn eval(arguments: &Vec<Expression>) -> Expression {
             a: a * 0.5,
             k: k * 0.5,
             mol: mol * 0.5,
+            cd: cd * 0.5,
+            other: other.scale(0.5),
         };
     } else {
         return Expression::FnCall {
@@ -35,19 +48,37 @@ pub fn eval(arguments: &Vec<Expression>) -> Expression {
 }
 
 /// Formats a square root.
-pub fn format(arguments: &Vec<Expression>) -> String {
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
     if arguments.len() != 1 {
         return String::new();
     }
 
-    format!("sqrt({})", arguments[0])
+    format!("sqrt({})", arguments[0].format(settings))
 }
 
 /// Converts a square root to LaTeX.
-pub fn latex(arguments: &Vec<Expression>) -> String {
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    if arguments.len() != 1 {
+        return String::new();
+    }
+
+    format!("\\sqrt{{{}}}", arguments[0].latex(true, settings))
+}
+
+/// Converts a square root to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
     if arguments.len() != 1 {
         return String::new();
     }
 
-    format!("\\sqrt{{{}}}", arguments[0])
-}
\ No newline at end of file
+    format!("<msqrt>{}</msqrt>", arguments[0].mathml(true, settings))
+}
+
+/// Converts a square root to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    if arguments.len() != 1 {
+        return String::new();
+    }
+
+    format!("sqrt({})", arguments[0].typst(true, settings))
+}
@@ -0,0 +1,59 @@
+//! Defines the sample standard deviation for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Error,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    expect_arguments,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    unpack,
+    variance::variance,
+    with_unit,
+};
+
+/// Evaluates the sample standard deviation of a vector.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("stdev", arguments, 1) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    let (values, unit) = match unpack("stdev", &arguments[0]) {
+        Some (v) => v,
+        None => return Expression::Null,
+    };
+
+    if values.len() < 2 {
+        Error::InvalidArgument ("stdev", "expected at least two values").warn();
+        return Expression::Null;
+    }
+
+    with_unit(variance(&values).sqrt(), &unit)
+}
+
+/// Formats a sample standard deviation.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("stdev", arguments, settings)
+}
+
+/// Converts a sample standard deviation to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("stdev", arguments, settings)
+}
+
+/// Converts a sample standard deviation to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("stdev", arguments, settings)
+}
+
+/// Converts a sample standard deviation to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("stdev", arguments, settings)
+}
@@ -0,0 +1,67 @@
+//! Defines the unit function for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    Error,
+    escape_html,
+    Expression,
+    parser::unit_value,
+    typst::typst_string,
+    UnitTable,
+};
+
+use super::{
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+};
+
+/// Evaluates a unit given by name (e.g. `unit(kN)` or `unit("m/s^2")`),
+/// regardless of any variable of the same name.
+pub fn eval(arguments: &[Expression], units: &UnitTable) -> Expression {
+    match name(arguments).and_then(|n| unit_value(n, units)) {
+        Some (u) => u,
+        None => {
+            Error::InvalidArgument ("unit", &format_call("unit", arguments, &DisplaySettings::default())).warn();
+            Expression::Null
+        },
+    }
+}
+
+/// Gets the name of the unit requested by the arguments of a unit function.
+fn name(arguments: &[Expression]) -> Option<&str> {
+    match arguments {
+        [Expression::Identifier (s)] | [Expression::Text (s)] => Some (s),
+        _ => None,
+    }
+}
+
+/// Formats a unit.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("unit", arguments, settings)
+}
+
+/// Converts a unit to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match name(arguments) {
+        Some (s) => format!("\\mathrm{{{}}}", s),
+        None => latex_call("unit", arguments, settings),
+    }
+}
+
+/// Converts a unit to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match name(arguments) {
+        Some (s) => format!("<mi mathvariant=\"normal\">{}</mi>", escape_html(s)),
+        None => mathml_call("unit", arguments, settings),
+    }
+}
+
+/// Converts a unit to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    match name(arguments) {
+        Some (s) => typst_string(s),
+        None => typst_call("unit", arguments, settings),
+    }
+}
@@ -0,0 +1,70 @@
+//! Defines the sample variance for the Carlo language.
+
+use crate::{
+    DisplaySettings,
+    BinaryOperation,
+    Error,
+    Expression,
+    UnitTable,
+};
+
+use super::{
+    expect_arguments,
+    latex_call,
+    mathml_call,
+    typst_call,
+    format_call,
+    mean::mean,
+    unpack,
+    with_unit,
+};
+
+/// Evaluates the sample variance of a vector.
+/// The variance has the square of the units of the vector.
+pub fn eval(arguments: &[Expression], _units: &UnitTable) -> Expression {
+    let arguments = match expect_arguments("variance", arguments, 1) {
+        Some (a) => a,
+        None => return Expression::Null,
+    };
+
+    let (values, unit) = match unpack("variance", &arguments[0]) {
+        Some (v) => v,
+        None => return Expression::Null,
+    };
+
+    if values.len() < 2 {
+        Error::InvalidArgument ("variance", "expected at least two values").warn();
+        return Expression::Null;
+    }
+
+    with_unit(variance(&values), &BinaryOperation::Mul.simplify(&unit, &unit))
+}
+
+/// Computes the sample variance of a list of values.
+pub fn variance(values: &[f64]) -> f64 {
+    let mean = mean(values);
+
+    values.iter()
+        .map(|v| (v - mean).powi(2))
+        .sum::<f64>() / (values.len() - 1) as f64
+}
+
+/// Formats a sample variance.
+pub fn format(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    format_call("variance", arguments, settings)
+}
+
+/// Converts a sample variance to LaTeX.
+pub fn latex(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    latex_call("var", arguments, settings)
+}
+
+/// Converts a sample variance to MathML.
+pub fn mathml(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    mathml_call("var", arguments, settings)
+}
+
+/// Converts a sample variance to Typst.
+pub fn typst(arguments: &[Expression], settings: &DisplaySettings) -> String {
+    typst_call("var", arguments, settings)
+}
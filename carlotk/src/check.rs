@@ -0,0 +1,374 @@
+//! Static dimensional analysis for the Carlo language.
+
+use std::{
+    collections::HashMap,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use crate::{
+    Annotation,
+    BinaryOperation,
+    Error,
+    Expression,
+    UnitTable,
+    carlo_std,
+    dimension::describe,
+    parser::unit_value,
+};
+
+#[derive(Default)]
+/// Infers the dimensions of the variables and expressions in a program
+/// without evaluating it, collecting any dimensional errors.
+pub struct Checker {
+    /// Dimensions of variables (as floats of value 1), or `None` if unknown
+    variables: HashMap<String, Option<Expression>>,
+
+    /// Variables in the order in which they are first assigned
+    order: Vec<String>,
+
+    /// Dimension annotations of variables
+    annotations: HashMap<String, Annotation>,
+
+    /// Units known to the program, including declared units
+    units: UnitTable,
+
+    /// Directory of the source file, in which data files are found
+    directory: Option<PathBuf>,
+
+    /// Errors found so far
+    errors: Vec<Error<String>>,
+}
+
+/// Checks a program before it is evaluated, reporting any
+/// errors and exiting if there are any.
+pub fn check_program(statements: &[(usize, Expression)], inputfile: Option<&Path>) {
+    let mut checker = Checker::new();
+    if let Some (f) = inputfile {
+        checker.set_source(f);
+    }
+    checker.check(statements);
+
+    let errors = checker.errors();
+
+    if !errors.is_empty() {
+        for error in errors {
+            error.report();
+        }
+
+        Error::FailedCheck (errors.len()).throw();
+    }
+}
+
+impl Checker {
+    /// Constructs a new checker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks a series of statements, each with the line on which it begins.
+    pub fn check(&mut self, statements: &[(usize, Expression)]) {
+        for (line, statement) in statements {
+            self.dimensions(statement, *line);
+        }
+    }
+
+    /// Sets the source file being checked, relative to which data files
+    /// (e.g. `csv("data.csv")`) are found.
+    pub fn set_source(&mut self, inputfile: &Path) {
+        self.directory = inputfile.parent().map(|d| d.to_path_buf());
+    }
+
+    /// Gets the errors found by this checker.
+    pub fn errors(&self) -> &[Error<String>] {
+        &self.errors
+    }
+
+    /// Gets each variable with a description of its dimensions,
+    /// in the order in which they were first assigned.
+    pub fn variables(&self) -> Vec<(String, String)> {
+        self.order.iter()
+            .map(|v| (v.to_owned(), match self.variables.get(v) {
+                Some (Some (d)) => describe(d),
+                _ => String::from("unknown"),
+            }))
+            .collect::<Vec<(String, String)>>()
+    }
+
+    /// Assigns dimensions to a variable, checking them against its annotation.
+    fn assign(&mut self, name: &str, dimensions: Option<Expression>, line: usize) {
+        if let (Some (a), Some (d)) = (self.annotations.get(name), &dimensions) {
+            if !a.matches(d) {
+                self.errors.push(Error::MismatchedDimensions (
+                    line.to_string(),
+                    name.to_string(),
+                    a.text.to_owned(),
+                    describe(d),
+                ));
+            }
+        }
+
+        if !self.variables.contains_key(name) {
+            self.order.push(name.to_string());
+        }
+
+        self.variables.insert(name.to_string(), dimensions);
+    }
+
+    /// Gets the dimensions of a variable or unit.
+    fn lookup(&self, name: &str) -> Option<Expression> {
+        match self.variables.get(name) {
+            Some (d) => d.to_owned(),
+            None => unit_value(name, &self.units).and_then(|u| unity(&u)),
+        }
+    }
+
+    /// Infers the dimensions of an expression as a float of value 1,
+    /// returning `None` if they cannot be inferred.
+    fn dimensions(&mut self, expr: &Expression, line: usize) -> Option<Expression> {
+        use Expression::*;
+
+        match expr {
+            Assignment {
+                left,
+                right,
+                annotation,
+                ..
+            } => {
+                match annotation {
+                    Some (a) => self.annotations.insert(left.to_owned(), a.to_owned()),
+                    None => self.annotations.remove(left),
+                };
+
+                if let Some (Annotation { text, dimensions: None }) = annotation {
+                    self.errors.push(Error::InvalidAnnotation (line.to_string(), text.to_owned()));
+                }
+
+                let dimensions = self.dimensions(right, line);
+                self.assign(left, dimensions, line);
+                None
+            },
+            Reassignment {
+                left,
+                right,
+                ..
+            } => {
+                let dimensions = self.dimensions(right, line);
+                self.assign(left, dimensions, line);
+                None
+            },
+            Float { .. } => unity(expr),
+            Identifier (s) | Symbolic (s) | FullSymbolic (s) | ShowWork (s) => self.lookup(s),
+            BinOp {
+                left,
+                oper,
+                right,
+            } => {
+                let l = self.dimensions(left, line);
+                let r = self.dimensions(right, line);
+
+                match (oper, l, r) {
+                    (BinaryOperation::Add | BinaryOperation::Sub, Some (l), Some (r)) => {
+                        if !l.same_dimensions(&r) {
+                            let verb = match oper {
+                                BinaryOperation::Add => "add",
+                                _ => "subtract",
+                            };
+
+                            self.errors.push(Error::MismatchedOperands (
+                                line.to_string(),
+                                verb.to_string(),
+                                describe(&l),
+                                describe(&r),
+                            ));
+                        }
+
+                        Some (l)
+                    },
+                    (BinaryOperation::Add | BinaryOperation::Sub, l, r) => l.or(r),
+                    (_, Some (l), Some (r)) => Some (oper.simplify(&l, &r)),
+                    _ => None,
+                }
+            },
+            FnCall {
+                name,
+                arguments,
+            } => {
+                // Units are known statically (e.g. `unit(kN)`)
+                if name == "unit" {
+                    return unity(&carlo_std::call(name, arguments, &self.units));
+                }
+
+                // Data files must be readable and well-formed
+                if name == "csv" {
+                    let arguments = carlo_std::locate_csv(arguments, self.directory.as_deref());
+
+                    if let Err (e) = carlo_std::read_csv(&arguments, &self.units) {
+                        self.errors.push(e);
+                    }
+
+                    return None;
+                }
+
+                let arguments = arguments.iter()
+                    .map(|a| self.dimensions(a, line))
+                    .collect::<Vec<Option<Expression>>>();
+
+                if carlo_std::DIMENSIONLESS_ARGUMENTS.contains(&name.as_str()) {
+                    for a in arguments.iter().flatten().filter(|a| !a.is_dimensionless()) {
+                        self.errors.push(Error::MismatchedArgument (
+                            line.to_string(),
+                            name.to_owned(),
+                            describe(a),
+                        ));
+                    }
+                }
+
+                // Some arguments must share dimensions (e.g. `x` and `xs` of `interp`)
+                if let Some ((_, positions)) = carlo_std::SAME_DIMENSIONS.iter().find(|(f, _)| f == name) {
+                    let shared = arguments.iter()
+                        .enumerate()
+                        .filter(|(i, _)| positions.is_none_or(|p| p.contains(i)))
+                        .filter_map(|(_, a)| a.as_ref())
+                        .collect::<Vec<&Expression>>();
+
+                    if let Some (d) = shared.iter().find(|d| !d.same_dimensions(shared[0])) {
+                        self.errors.push(Error::MismatchedArguments (
+                            line.to_string(),
+                            name.to_owned(),
+                            describe(shared[0]),
+                            describe(d),
+                        ));
+                    }
+                }
+
+                carlo_std::dimensions(name, &arguments, &self.units)
+            },
+            NamedArgument {
+                value,
+                ..
+            } => self.dimensions(value, line),
+            Formatted {
+                value,
+                ..
+            } | Ratio {
+                value,
+                ..
+            } => self.dimensions(value, line),
+            Vector (v) => {
+                // A vector has the dimensions of its elements if they all agree
+                let elements = v.iter()
+                    .map(|e| self.dimensions(e, line))
+                    .collect::<Option<Vec<Expression>>>()?;
+
+                let first = elements.first()?;
+
+                match elements.iter().find(|e| !e.same_dimensions(first)) {
+                    Some (e) => {
+                        self.errors.push(Error::MismatchedElements (
+                            line.to_string(),
+                            describe(first),
+                            describe(e),
+                        ));
+                        None
+                    },
+                    None => Some (first.to_owned()),
+                }
+            },
+            UnitDeclaration {
+                name,
+                value,
+            } => {
+                match value {
+                    Some (v) => self.units.define(name, *v.to_owned()),
+                    None => {
+                        self.units.define_base(name);
+                    },
+                }
+
+                None
+            },
+            Dimension (d) => {
+                self.units.declare_dimension(d);
+                None
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Gets the dimensions of a numeric expression (or of the elements of a vector)
+/// as a float of value 1.
+fn unity(expr: &Expression) -> Option<Expression> {
+    match expr {
+        Expression::Vector (v) => unity(v.first()?),
+        e => carlo_std::unit_of(e),
+    }
+}
+
+#[test]
+fn test_check() {
+    let parser = crate::Parser::new(false);
+    let statements = parser.parse_located("let F = 2 kN\nlet L: Length = 3 m\nlet M = F * L\nM + F\nlet s: Pressure = F / L\nL = 2 s + 1 m\nexp(F / M)\n");
+
+    let mut checker = Checker::new();
+    checker.check(&statements);
+
+    let errors = checker.errors()
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>();
+
+    assert_eq!(errors, vec![
+        "Mismatched dimensions at line 4: cannot add Energy and Force".to_string(),
+        "Mismatched dimensions at line 5: s is annotated as Pressure but has dimensions of Stiffness".to_string(),
+        "Mismatched dimensions at line 6: cannot add Time and Length".to_string(),
+        "Mismatched dimensions at line 6: L is annotated as Length but has dimensions of Time".to_string(),
+        "Mismatched dimensions at line 7: argument to function exp must be dimensionless but has dimensions of [length]^-1".to_string(),
+    ]);
+
+    assert_eq!(checker.variables()[2], ("M".to_string(), "Energy".to_string()));
+}
+
+#[test]
+fn test_annotations() {
+    let parser = crate::Parser::new(false);
+    let statements = parser.parse_located("let v: [length/time] = 3 s\nlet w: Bogus = 2 m\nlet x: Velocity = 2 m/s\nx = v / 1 s\nlet y: [length] / [time] = 2 m/s\n");
+
+    let mut checker = Checker::new();
+    checker.check(&statements);
+
+    let errors = checker.errors()
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>();
+
+    // Invalid annotations are errors, and valid annotations are checked on reassignment
+    assert_eq!(errors, vec![
+        "Invalid annotation at line 1: [length/time]".to_string(),
+        "Invalid annotation at line 2: Bogus".to_string(),
+        "Mismatched dimensions at line 4: x is annotated as Velocity but has dimensions of Dimensionless".to_string(),
+    ]);
+}
+
+#[test]
+fn test_function_arguments() {
+    let parser = crate::Parser::new(false);
+    let statements = parser.parse_located("let t = [1 s, 2 s]\nlet x = [1 m, 2 m]\nmedian([1 m, 2 s])\ninterp(1 m, t, x)\ninterp(1 s, t, x)\nmax(1 m, 2 m, 3 s)\nlinspace(0 m, 1 m, 5)\n");
+
+    let mut checker = Checker::new();
+    checker.check(&statements);
+
+    let errors = checker.errors()
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>();
+
+    assert_eq!(errors, vec![
+        "Mismatched dimensions at line 3: vector has elements with dimensions of Length and Time".to_string(),
+        "Mismatched dimensions at line 4: arguments to function interp have dimensions of Length and Time".to_string(),
+        "Mismatched dimensions at line 6: arguments to function max have dimensions of Length and Time".to_string(),
+    ]);
+}
@@ -15,8 +15,37 @@ pub enum Flag {
 
     /// Provides help to the user
     Help,
+
+    /// Evaluates using exact rational arithmetic
+    Exact,
+
+    /// Runs without static dimensional analysis
+    NoCheck,
+
+    /// Emits LaTeX without a table of contents
+    NoToc,
+
+    /// Emits LaTeX units with the macros of the `siunitx` package
+    Siunitx,
+
+    /// Builds a PDF from the emitted LaTeX
+    Pdf,
 }
 
+/// Options for the Carlo language executable, each followed by a value
+/// (e.g. `--units mm-N-MPa`).
+const OPTIONS: [&str; 9] = [
+    "units",
+    "title",
+    "author",
+    "date",
+    "template",
+    "preamble",
+    "precision",
+    "engine",
+    "output-dir",
+];
+
 /// Converts a string into a flag.
 impl From<&str> for Flag {
     fn from(input: &str) -> Self {
@@ -25,6 +54,11 @@ impl From<&str> for Flag {
         match input {
             "debug" => Debug,
             "help" => Help,
+            "exact" => Exact,
+            "no-check" => NoCheck,
+            "no-toc" => NoToc,
+            "siunitx" => Siunitx,
+            "pdf" => Pdf,
             _ => Error::UnrecognizedFlag (input).throw(),
         }
     }
@@ -56,6 +90,9 @@ pub struct CliArgs {
 
     /// Flags
     pub flags: Vec<Flag>,
+
+    /// Options with their values
+    pub options: Vec<(String, String)>,
 }
 
 impl CliArgs {
@@ -80,8 +117,9 @@ impl CliArgs {
             inputfile = Some (args[2].as_str().into());
         }
 
-        // Parse flags
+        // Parse flags and options
         let mut flags = Vec::new();
+        let mut options = Vec::new();
         let mut i = if let Some (_) = &inputfile {
             3
         } else if let Some(_) = &argument {
@@ -92,7 +130,14 @@ impl CliArgs {
         while i < args.len() {
             let arg = &args[i];
 
-            if arg.starts_with("--") {
+            if arg.starts_with("--") && OPTIONS.contains(&&arg[2..]) {
+                match args.get(i + 1) {
+                    Some (v) => options.push((arg[2..].to_string(), v.to_owned())),
+                    None => Error::MissingOptionValue (arg).throw(),
+                }
+
+                i += 1;
+            } else if arg.starts_with("--") {
                 flags.push(arg[2..].into());
             } else if arg.starts_with("-") {
                 for c in arg[1..].chars() {
@@ -110,6 +155,7 @@ impl CliArgs {
             argument,
             inputfile,
             flags,
+            options,
         }
     }
 
@@ -123,4 +169,11 @@ impl CliArgs {
 
         return false;
     }
+
+    /// Gets the value of an option, if it was provided.
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
 }
\ No newline at end of file
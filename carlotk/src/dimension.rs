@@ -0,0 +1,207 @@
+//! Defines dimensions, including base dimensions beyond the SI base dimensions,
+//! and dimension annotations on variables.
+
+use crate::{
+    Expression,
+    unit::UnitDefinition,
+};
+
+/// Defines the names of the SI base dimensions, in the order of the powers
+/// of `Expression::Float` (e.g. `[length]`).
+pub const BASE_DIMENSIONS: [&str; 7] = [
+    "mass",
+    "length",
+    "time",
+    "current",
+    "temperature",
+    "amount",
+    "luminous_intensity",
+];
+
+/// Defines the named quantities available to dimension annotations (e.g. `Pressure`).
+/// Each quantity is structured as (name, (1, kg, m, s, A, K, mol, cd))
+pub const QUANTITIES: [UnitDefinition; 32] = [
+    ("Dimensionless", (1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Mass", (1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Length", (1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Time", (1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Current", (1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0)),
+    ("Temperature", (1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0)),
+    ("Amount", (1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0)),
+    ("LuminousIntensity", (1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0)),
+    ("Area", (1.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Volume", (1.0, 0.0, 3.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Velocity", (1.0, 0.0, 1.0, -1.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Acceleration", (1.0, 0.0, 1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Frequency", (1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Density", (1.0, 1.0, -3.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Force", (1.0, 1.0, 1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Pressure", (1.0, 1.0, -1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Stress", (1.0, 1.0, -1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Energy", (1.0, 1.0, 2.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Moment", (1.0, 1.0, 2.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Power", (1.0, 1.0, 2.0, -3.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Stiffness", (1.0, 1.0, 0.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Charge", (1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0)),
+    ("Voltage", (1.0, 1.0, 2.0, -3.0, -1.0, 0.0, 0.0, 0.0)),
+    ("Resistance", (1.0, 1.0, 2.0, -3.0, -2.0, 0.0, 0.0, 0.0)),
+    ("Capacitance", (1.0, -1.0, -2.0, 4.0, 2.0, 0.0, 0.0, 0.0)),
+    ("Conductance", (1.0, -1.0, -2.0, 3.0, 2.0, 0.0, 0.0, 0.0)),
+    ("MagneticFlux", (1.0, 1.0, 2.0, -2.0, -1.0, 0.0, 0.0, 0.0)),
+    ("MagneticFluxDensity", (1.0, 1.0, 0.0, -2.0, -1.0, 0.0, 0.0, 0.0)),
+    ("Inductance", (1.0, 1.0, 2.0, -2.0, -2.0, 0.0, 0.0, 0.0)),
+    ("Illuminance", (1.0, 0.0, -2.0, 0.0, 0.0, 0.0, 0.0, 1.0)),
+    ("AbsorbedDose", (1.0, 0.0, 2.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("CatalyticActivity", (1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 1.0, 0.0)),
+];
+
+/// Rounds the power of a dimension to remove floating-point residue
+/// (e.g. so that the powers of `m^(1/3)` cubed cancel exactly).
+pub fn round_power(pow: f64) -> f64 {
+    (pow * 1E9).round() / 1E9
+}
+
+#[derive(Clone, Debug)]
+/// A dimension annotation on a variable (e.g. `Pressure` in `let sigma: Pressure = F / A`).
+pub struct Annotation {
+    /// Annotation as written
+    pub text: String,
+
+    /// A value with the annotated dimensions, or `None` if the annotation is invalid
+    pub dimensions: Option<Box<Expression>>,
+}
+
+impl Annotation {
+    /// Checks if a value has the annotated dimensions.
+    /// Values that are not numeric (e.g. text) and invalid annotations are not checked.
+    pub fn matches(&self, value: &Expression) -> bool {
+        let dimensions = match &self.dimensions {
+            Some (d) => d,
+            None => return true,
+        };
+
+        match value {
+            Expression::Float { .. } => value.same_dimensions(dimensions),
+            Expression::Vector (v) => v.iter().all(|e| self.matches(e)),
+            Expression::Formatted { value, .. } | Expression::Ratio { value, .. } => self.matches(value),
+            _ => true,
+        }
+    }
+}
+
+/// Describes the dimensions of a value, by the name of a quantity if one matches
+/// (e.g. `Force`) or otherwise by its base dimensions (e.g. `[length] [time]^-1`).
+pub fn describe(value: &Expression) -> String {
+    let (powers, other) = match value {
+        Expression::Float { kg, m, s, a, k, mol, cd, other, .. } => ([*kg, *m, *s, *a, *k, *mol, *cd], other),
+        Expression::Vector (v) if !v.is_empty() => return describe(&v[0]),
+        Expression::Formatted { value, .. } | Expression::Ratio { value, .. } => return describe(value),
+        _ => return String::from("no dimensions"),
+    };
+
+    if other.is_empty() {
+        for (name, (_, kg, m, s, a, k, mol, cd)) in QUANTITIES {
+            if powers == [kg, m, s, a, k, mol, cd] {
+                return name.to_string();
+            }
+        }
+    }
+
+    BASE_DIMENSIONS.iter()
+        .zip(powers)
+        .map(|(d, pow)| (d.to_string(), pow))
+        .chain(other.iter().cloned())
+        .filter(|(_, pow)| *pow != 0.0)
+        .map(|(d, pow)| if pow == 1.0 {
+            format!("[{}]", d)
+        } else {
+            format!("[{}]^{}", d, pow)
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Powers of additional base dimensions (e.g. `currency`),
+/// sorted by name and without zero powers.
+pub struct Dimensions (Vec<(String, f64)>);
+
+impl Dimensions {
+    /// Constructs an empty set of dimensions.
+    pub fn new() -> Self {
+        Self (Vec::new())
+    }
+
+    /// Constructs the first power of a base dimension.
+    pub fn base(name: &str) -> Self {
+        Self (vec![(name.to_string(), 1.0)])
+    }
+
+    /// Checks if there are no additional dimensions.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the dimensions and their powers.
+    pub fn iter(&self) -> impl Iterator<Item = &(String, f64)> {
+        self.0.iter()
+    }
+
+    /// Adds `sign` times the powers of another set of dimensions to these powers
+    /// (multiplying units when `sign` is 1 and dividing units when `sign` is -1).
+    pub fn combine(&self, other: &Self, sign: f64) -> Self {
+        let mut powers = self.0.clone();
+
+        for (name, pow) in other.iter() {
+            match powers.iter_mut().find(|(n, _)| n == name) {
+                Some ((_, p)) => *p = round_power(*p + sign * pow),
+                None => powers.push((name.to_owned(), round_power(sign * pow))),
+            }
+        }
+
+        powers.retain(|(_, p)| *p != 0.0);
+        powers.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Self (powers)
+    }
+
+    /// Multiplies each power by a factor.
+    pub fn scale(&self, factor: f64) -> Self {
+        Self::new().combine(self, factor)
+    }
+}
+
+#[test]
+fn test_dimensions() {
+    let currency = Dimensions::base("currency");
+    let per_item = Dimensions::base("item").scale(-1.0);
+
+    // Powers are combined, sorted by name, and cancelled
+    let price = currency.combine(&per_item, 1.0);
+    assert_eq!(price.iter().cloned().collect::<Vec<(String, f64)>>(), [
+        ("currency".to_string(), 1.0),
+        ("item".to_string(), -1.0),
+    ]);
+    assert!(price.combine(&price, -1.0).is_empty());
+
+    let value = Expression::Float {
+        value: crate::Number::exact(1.0),
+        kg: 0.0,
+        m: 0.0,
+        s: -1.0,
+        a: 0.0,
+        k: 0.0,
+        mol: 0.0,
+        cd: 0.0,
+        other: price,
+    };
+
+    assert_eq!(describe(&value), "[time]^-1 [currency] [item]^-1");
+
+    // Quantities of luminous intensity
+    let units = crate::UnitTable::default();
+    let candela = crate::parser::unit_value("cd", &units).unwrap();
+    let lux = crate::parser::unit_value("lx", &units).unwrap();
+    assert_eq!(describe(&candela), "LuminousIntensity");
+    assert_eq!(describe(&lux), "Illuminance");
+}
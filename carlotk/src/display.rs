@@ -0,0 +1,226 @@
+//! Defines display settings for numeric output.
+
+use std::collections::HashMap;
+
+use crate::{
+    Number,
+    UnitSystem,
+    UnitTable,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// Enumerates the notations available for displaying numbers.
+pub enum Notation {
+    /// Fixed number of decimal places
+    Fixed (usize),
+
+    /// Fixed number of significant figures
+    Significant (usize),
+
+    /// Scientific notation with a number of significant figures
+    Scientific (usize),
+
+    /// Engineering notation (exponent a multiple of 3) with a number of significant figures
+    Engineering (usize),
+}
+
+impl Notation {
+    /// Parses a notation from its name and number of digits (e.g. `sig 3`).
+    pub fn parse(name: &str, digits: Option<&str>) -> Option<Self> {
+        use Notation::*;
+
+        let digits = match digits {
+            Some (d) => str::parse::<usize>(d).ok()?,
+            None => 4,
+        };
+
+        let notation = match name {
+            "fixed" => Fixed (digits),
+            "sig" => Significant (digits.max(1)),
+            "sci" => Scientific (digits.max(1)),
+            "eng" => Engineering (digits.max(1)),
+            _ => return None,
+        };
+
+        Some (notation)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+/// Settings used when displaying values as text or LaTeX.
+pub struct DisplaySettings {
+    /// Numeric notation (or `None` to display approximations
+    /// to 4 decimal places and exact numbers exactly)
+    pub notation: Option<Notation>,
+
+    /// Choose SI prefixes automatically (e.g. `3.45 mm` rather than `0.00345 m`)
+    pub prefixes: bool,
+
+    /// Display information in bytes with binary prefixes (e.g. `1.5 MiB`) rather than bits
+    pub bytes: bool,
+
+    /// Units available for display, including declared units
+    pub units: UnitTable,
+
+    /// Unit system used to choose the units of values
+    pub system: UnitSystem,
+
+    /// Render values and units in LaTeX with the macros of the `siunitx` package
+    pub siunitx: bool,
+
+    /// LaTeX written for variables in place of their names (e.g. `\sigma_{\max}` for `sigma_max`)
+    pub names: HashMap<String, String>,
+}
+
+impl DisplaySettings {
+    /// Applies the arguments of a `display` pragma (e.g. `eng 3` or `default`),
+    /// returning `false` if they could not be parsed.
+    pub fn apply(&mut self, arguments: &[String]) -> bool {
+        let name = match arguments.first() {
+            Some (n) => n.as_str(),
+            None => return false,
+        };
+
+        if name == "default" {
+            self.notation = None;
+            return true;
+        }
+
+        match Notation::parse(name, arguments.get(1).map(|d| d.as_str())) {
+            Some (n) => {
+                self.notation = Some (n);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Constructs a copy of these settings with a different notation.
+    pub fn with_notation(&self, notation: Notation) -> Self {
+        let mut settings = self.clone();
+        settings.notation = Some (notation);
+        settings
+    }
+
+    /// Formats a number as text.
+    pub fn format_number(&self, value: &Number) -> String {
+        match self.notation {
+            None => format!("{:.4}", value),
+            Some (n) => match split(value.to_f64(), n) {
+                (mantissa, Some (exp)) => format!("{}e{}", mantissa, exp),
+                (mantissa, None) => mantissa,
+            },
+        }
+    }
+
+    /// Formats a number as LaTeX.
+    pub fn latex_number(&self, value: &Number) -> String {
+        match self.notation {
+            None => value.latex(4),
+            Some (n) => match split(value.to_f64(), n) {
+                (mantissa, Some (exp)) => format!("{} \\times 10^{{{}}}", mantissa, exp),
+                (mantissa, None) => mantissa,
+            },
+        }
+    }
+
+    /// Formats a number as MathML.
+    pub fn mathml_number(&self, value: &Number) -> String {
+        match self.notation {
+            None => value.mathml(4),
+            Some (n) => match split(value.to_f64(), n) {
+                (mantissa, Some (exp)) => format!(
+                    "<mn>{}</mn><mo>&#xD7;</mo><msup><mn>10</mn><mn>{}</mn></msup>",
+                    mantissa,
+                    exp,
+                ),
+                (mantissa, None) => format!("<mn>{}</mn>", mantissa),
+            },
+        }
+    }
+
+    /// Formats a number as Typst.
+    pub fn typst_number(&self, value: &Number) -> String {
+        match self.notation {
+            None => value.typst(4),
+            Some (n) => match split(value.to_f64(), n) {
+                (mantissa, Some (exp)) => format!("{} times 10^({})", mantissa, exp),
+                (mantissa, None) => mantissa,
+            },
+        }
+    }
+}
+
+/// Gets the decimal exponent of a number after rounding to `digits` significant figures.
+fn exponent(value: f64, digits: usize) -> i32 {
+    if value == 0.0 || !value.is_finite() {
+        return 0;
+    }
+
+    let sci = format!("{:.*e}", digits - 1, value);
+
+    match sci.split_once('e') {
+        Some ((_, e)) => str::parse::<i32>(e).unwrap_or(0),
+        None => 0,
+    }
+}
+
+/// Splits a number into a mantissa and an optional decimal exponent.
+fn split(value: f64, notation: Notation) -> (String, Option<i32>) {
+    use Notation::*;
+
+    match notation {
+        Fixed (d) => (format!("{:.*}", d, value), None),
+        Significant (n) => {
+            let e = exponent(value, n);
+
+            // Numbers with more integer digits than significant figures (and very
+            // small numbers) are displayed in scientific notation
+            if e < -4 || e >= n as i32 {
+                split(value, Scientific (n))
+            } else {
+                let decimals = (n as i32 - 1 - e) as usize;
+                (format!("{:.*}", decimals, value), None)
+            }
+        },
+        Scientific (n) => {
+            let e = exponent(value, n);
+            let mantissa = value / 10f64.powi(e);
+            (format!("{:.*}", n - 1, mantissa), Some (e))
+        },
+        Engineering (n) => {
+            let e = exponent(value, n);
+            let e3 = 3 * e.div_euclid(3);
+            let mantissa = value / 10f64.powi(e3);
+            let decimals = (n as i32 - 1 - (e - e3)).max(0) as usize;
+
+            match e3 {
+                0 => (format!("{:.*}", decimals, mantissa), None),
+                _ => (format!("{:.*}", decimals, mantissa), Some (e3)),
+            }
+        },
+    }
+}
+
+#[test]
+fn test_notation() {
+    let settings = |n| DisplaySettings {
+        notation: Some (n),
+        ..DisplaySettings::default()
+    };
+
+    let avogadro = Number::from(6.02214e23);
+    let small = Number::from(1E-9);
+
+    assert_eq!(settings(Notation::Fixed (2)).format_number(&Number::from(1.23456)), "1.23");
+    assert_eq!(settings(Notation::Significant (3)).format_number(&Number::from(0.012345)), "0.0123");
+    assert_eq!(settings(Notation::Significant (3)).format_number(&avogadro), "6.02e23");
+    assert_eq!(settings(Notation::Significant (3)).format_number(&Number::from(12345.678)), "1.23e4");
+    assert_eq!(settings(Notation::Significant (5)).format_number(&Number::from(12345.678)), "12346");
+    assert_eq!(settings(Notation::Significant (3)).format_number(&Number::from(999.6)), "1.00e3");
+    assert_eq!(settings(Notation::Scientific (3)).format_number(&small), "1.00e-9");
+    assert_eq!(settings(Notation::Engineering (3)).format_number(&avogadro), "602e21");
+    assert_eq!(settings(Notation::Engineering (3)).format_number(&Number::from(0.00345)), "3.45e-3");
+    assert_eq!(settings(Notation::Engineering (4)).format_number(&Number::from(999.96)), "1.000e3");
+    assert_eq!(settings(Notation::Engineering (3)).latex_number(&small), "1.00 \\times 10^{-9}");
+}
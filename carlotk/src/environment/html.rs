@@ -0,0 +1,184 @@
+//! Renders evaluated statements as an HTML document body with MathML.
+
+use crate::{
+    Expression,
+    escape_html,
+    mathml::{
+        math,
+        mathml_identifier,
+    },
+};
+
+use super::{
+    Environment,
+    Resolution,
+};
+
+/// Heading in the table of contents, with its level, anchor, and title
+type Heading = (usize, String, String);
+
+impl Environment {
+    /// Evaluates a series of statements in this environment and returns the body
+    /// of an HTML document: a table of contents built from headers, the statements
+    /// and their results as MathML, and a summary of every variable.
+    ///
+    /// Worked results are collapsed to their value, and expand to show the derivation.
+    pub fn html_evaluate(&mut self, expressions: &Vec<Expression>) -> String {
+        use Expression::*;
+
+        let mut headings: Vec<Heading> = Vec::new();
+        let mut names: Vec<String> = Vec::new();
+
+        let mut body = self.document(expressions, |out, display| {
+            if let Assignment { left, .. } | Reassignment { left, .. } | Work { left, .. } = out {
+                if !names.contains(left) {
+                    names.push(left.to_owned());
+                }
+            }
+
+            match out {
+                Header (s) => heading(&mut headings, 1, s),
+                Subheader (s) => heading(&mut headings, 2, s),
+                Subsubheader (s) => heading(&mut headings, 3, s),
+                Paragraph (s) => format!("<p>{}</p>\n", escape_html(s.trim())),
+                Work { left, value, .. } => format!(
+                    "<details>\n<summary>{}</summary>\n{}\n</details>\n",
+                    math(&format!("{}<mo>=</mo>{}", mathml_identifier(left, display), value.mathml(true, display)), false),
+                    math(&out.mathml(true, display), true),
+                ),
+                _ => format!("{}\n", math(&out.mathml(true, display), true)),
+            }
+        });
+
+        // Summarize the final value of every variable
+        let mut summary = String::new();
+        for name in &names {
+            if self.lookup(name).is_some() {
+                let value = self.simplify(&Identifier (name.to_owned()), Resolution::Numeric);
+                summary.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td></tr>\n",
+                    math(&mathml_identifier(name, &self.display), false),
+                    math(&value.mathml(true, &self.display), false),
+                ));
+            }
+        }
+
+        if !summary.is_empty() {
+            body.push_str(&heading(&mut headings, 1, "Variables"));
+            body.push_str(&format!(
+                "<table class=\"variables\">\n<thead><tr><th>Variable</th><th>Value</th></tr></thead>\n<tbody>\n{}</tbody>\n</table>\n",
+                summary,
+            ));
+        }
+
+        format!("{}<main>\n{}</main>\n", contents(&headings), body)
+    }
+}
+
+/// Adds a heading to the table of contents and converts it to HTML,
+/// with an anchor made from its title (e.g. `beam-design` for `Beam Design`).
+fn heading(headings: &mut Vec<Heading>, level: usize, title: &str) -> String {
+    // Repeated titles get numbered anchors
+    let repeats = headings.iter()
+        .filter(|(_, _, t)| slug(t) == slug(title))
+        .count();
+    let id = match repeats {
+        0 => slug(title),
+        n => format!("{}-{}", slug(title), n + 1),
+    };
+
+    let html = format!("<h{} id=\"{}\">{}</h{}>\n", level + 1, id, escape_html(title), level + 1);
+    headings.push((level, id, title.to_string()));
+
+    html
+}
+
+/// Converts a title to an anchor of lowercase words joined by hyphens.
+fn slug(title: &str) -> String {
+    title.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-")
+}
+
+/// Builds a navigable table of contents from a list of headings,
+/// with the list of each heading's subheadings inside its item.
+fn contents(headings: &[Heading]) -> String {
+    if headings.is_empty() {
+        return String::new();
+    }
+
+    let mut output = String::from("<nav>\n<h2>Contents</h2>\n");
+    let mut depth = 0;
+
+    // Whether the last item at the current depth is still open
+    let mut open = false;
+
+    for (level, id, title) in headings {
+        while depth > *level {
+            if open {
+                output.push_str("</li>\n");
+            }
+            output.push_str("</ul>\n");
+            depth -= 1;
+            open = true;
+        }
+
+        // Skipped levels get an item of their own to hold the list
+        while depth < *level {
+            if depth > 0 {
+                if !open {
+                    output.push_str("<li>");
+                }
+                output.push('\n');
+            }
+            output.push_str("<ul>\n");
+            depth += 1;
+            open = false;
+        }
+
+        if open {
+            output.push_str("</li>\n");
+        }
+
+        output.push_str(&format!("<li><a href=\"#{}\">{}</a>", id, escape_html(title)));
+        open = true;
+    }
+
+    while depth > 0 {
+        if open {
+            output.push_str("</li>\n");
+        }
+        output.push_str("</ul>\n");
+        depth -= 1;
+        open = true;
+    }
+
+    output.push_str("</nav>\n");
+
+    output
+}
+
+#[test]
+fn test_html() {
+    let parser = crate::Parser::new(false);
+    let mut env = Environment::new();
+
+    let html = env.html_evaluate(&parser.parse("@ Loads\n@@ Dead\nlet w = 2 kN / 1 m\n@ Loads\n#pragma work\nlet v = w * 2\n"));
+
+    assert!(html.starts_with("<nav>\n<h2>Contents</h2>\n<ul>\n<li><a href=\"#loads\">Loads</a>\n<ul>\n<li><a href=\"#dead\">Dead</a></li>\n</ul>\n</li>\n<li><a href=\"#loads-2\">Loads</a></li>\n<li><a href=\"#variables\">Variables</a></li>\n</ul>\n</nav>\n"));
+    assert!(html.contains("<h2 id=\"loads-2\">Loads</h2>"));
+    assert!(html.contains("<details>\n<summary><math><mrow><mi>v</mi><mo>=</mo><mn>4000.0000</mn>"));
+    assert!(html.contains("<tr><td><math><mrow><mi>v</mi></mrow></math></td><td><math><mrow><mn>4000.0000</mn>"));
+
+    // Accents, primes, and the LaTeX given for identifiers
+    let html = env.html_evaluate(&parser.parse("let x_dot = 2\nlet sigma_max_prime = 3\nlet tau_c \"\\tau_{\\mathrm{c}}\" = 4\n"));
+    assert!(html.contains("<math display=\"block\"><mrow><mover accent=\"true\"><mi>x</mi><mo>\u{2D9}</mo></mover><mo>:=</mo>"));
+    assert!(html.contains("<math display=\"block\"><mrow><msubsup><mi>σ</mi><mrow><mi>max</mi></mrow><mo>\u{2032}</mo></msubsup><mo>:=</mo>"));
+    assert!(html.contains("<math display=\"block\"><mrow><msub><mi>τ</mi><mrow><mi>c</mi></mrow></msub><mo>:=</mo>"));
+
+    // Skipped levels are nested in empty items
+    let headings = [(2, "a".to_string(), "A".to_string()), (1, "b".to_string(), "B".to_string())];
+    assert_eq!(contents(&headings), "<nav>\n<h2>Contents</h2>\n<ul>\n<li>\n<ul>\n<li><a href=\"#a\">A</a></li>\n</ul>\n</li>\n<li><a href=\"#b\">B</a></li>\n</ul>\n</nav>\n");
+}
@@ -1,11 +1,24 @@
 //! Environment manager for the Carlo language.
 
-use std::collections::HashMap;
+mod html;
+
+use std::{
+    collections::HashMap,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
 
 use crate::{
+    Annotation,
+    DisplaySettings,
     Error,
     Expression,
+    UnitSystem,
     carlo_std,
+    dimension::describe,
+    parser::unit_value,
 };
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -16,8 +29,33 @@ pub enum Resolution {
     Numeric,
 }
 
+/// Default number of significant digits kept when an inexact
+/// result (e.g. a square root) is converted to an exact number.
+const EXACT_PRECISION: usize = 15;
+
 pub struct Environment {
     variables: HashMap<String, Expression>,
+
+    /// Dimension annotations of variables
+    annotations: HashMap<String, Annotation>,
+
+    /// Evaluate using exact rational arithmetic
+    exact: bool,
+
+    /// Significant digits kept for inexact results in exact mode
+    precision: usize,
+
+    /// Settings used when displaying results
+    display: DisplaySettings,
+
+    /// Show the work for every result and assignment (`#pragma work`)
+    work: bool,
+
+    /// Directory of the source file, in which data files are found
+    directory: Option<PathBuf>,
+
+    /// Errors found while evaluating (e.g. mismatched dimensions or unreadable data files)
+    errors: Vec<Error<String>>,
 }
 
 impl Environment {
@@ -25,6 +63,117 @@ impl Environment {
     pub fn new() -> Self {
         Self {
             variables: HashMap::new(),
+            annotations: HashMap::new(),
+            exact: false,
+            precision: EXACT_PRECISION,
+            display: DisplaySettings::default(),
+            work: false,
+            directory: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Gets the errors found while evaluating in this environment.
+    pub fn errors(&self) -> &[Error<String>] {
+        &self.errors
+    }
+
+    /// Enables or disables exact rational arithmetic.
+    pub fn set_exact(&mut self, exact: bool) {
+        self.exact = exact;
+    }
+
+    /// Sets the unit system used to display values (e.g. `mm-N-MPa`)
+    /// from the `units` option, exiting if there is no such unit system.
+    pub fn set_unit_system(&mut self, name: &str) {
+        match UnitSystem::named(name) {
+            Some (s) => self.display.system = s,
+            None => Error::InvalidOptionValue ("units", name).throw(),
+        }
+    }
+
+    /// Sets the source file being evaluated, relative to which data files
+    /// (e.g. `csv("data.csv")`) are found.
+    pub fn set_source(&mut self, inputfile: &Path) {
+        self.directory = inputfile.parent().map(|d| d.to_path_buf());
+    }
+
+    /// Renders values and units in LaTeX with the macros of the `siunitx` package.
+    pub fn set_siunitx(&mut self, siunitx: bool) {
+        self.display.siunitx = siunitx;
+    }
+
+    /// Applies a pragma to this environment.
+    fn pragma(&mut self, name: &str, arguments: &[String]) {
+        match name {
+            "exact" => {
+                self.exact = true;
+
+                if let Some (a) = arguments.first() {
+                    match str::parse::<usize>(a) {
+                        Ok (p) if (1..=17).contains(&p) => self.precision = p,
+                        _ => Error::InvalidPragma (name, "expected between 1 and 17 significant digits").warn(),
+                    }
+                }
+            },
+            "inexact" => self.exact = false,
+            "display" => if !self.display.apply(arguments) {
+                Error::InvalidPragma (name, "expected `fixed`, `sig`, `sci`, `eng` or `default`, optionally followed by a number of digits").warn();
+            },
+            "work" => self.work = true,
+            "nowork" => self.work = false,
+            "prefixes" => self.display.prefixes = true,
+            "noprefixes" => self.display.prefixes = false,
+            "bytes" => self.display.bytes = true,
+            "bits" => self.display.bytes = false,
+            "units" => match arguments.first().and_then(|a| UnitSystem::named(a)) {
+                Some (s) => self.display.system = s,
+                None => Error::InvalidPragma (name, "expected `SI`, `mm-N-MPa` or `US`").warn(),
+            },
+            _ => Error::UnrecognizedPragma (name).warn(),
+        }
+    }
+
+    /// Converts the numbers in an expression to exact or inexact numbers,
+    /// depending on whether this environment uses exact arithmetic.
+    fn normalize(&self, expr: Expression) -> Expression {
+        use Expression::*;
+
+        match expr {
+            Float {
+                value,
+                kg,
+                m,
+                s,
+                a,
+                k,
+                mol,
+                cd,
+                other,
+            } => Float {
+                value: if self.exact {
+                    value.round_exact(self.precision)
+                } else {
+                    value.approx()
+                },
+                kg,
+                m,
+                s,
+                a,
+                k,
+                mol,
+                cd,
+                other,
+            },
+            Vector (v) => Vector (v.into_iter()
+                .map(|e| self.normalize(e))
+                .collect::<Vec<Expression>>()
+            ),
+            Table (t) => Table (t.into_iter()
+                .map(|(n, c)| (n, self.normalize(c)))
+                .collect::<Vec<(String, Expression)>>()
+            ),
+            e => e,
         }
     }
 
@@ -38,6 +187,119 @@ impl Environment {
         self.variables.get(name).cloned()
     }
 
+    /// Evaluates the value of a variable when it is assigned if it is a table
+    /// (e.g. `csv("data.csv")`), so that its data is not read again whenever it is used.
+    fn store(&mut self, name: &String, value: &Expression) -> Expression {
+        let value = match value {
+            Expression::FnCall { name, .. } if carlo_std::TABLES.contains(&name.as_str()) => {
+                self.simplify(value, Resolution::Numeric)
+            },
+            v => v.to_owned(),
+        };
+
+        self.register(name, &value);
+        value
+    }
+
+    /// Checks that the value assigned to a variable has the dimensions
+    /// of its annotation, if it has one.
+    fn check_annotation(&mut self, name: &str, value: &Expression, line: usize) {
+        let annotation = match self.annotations.get(name) {
+            Some (a) => a.to_owned(),
+            None => return,
+        };
+
+        let value = self.simplify(value, Resolution::Numeric);
+
+        if !annotation.matches(&value) {
+            let error = Error::MismatchedDimensions (
+                line.to_string(),
+                name.to_string(),
+                annotation.text.to_owned(),
+                describe(&value),
+            );
+
+            error.report();
+            self.errors.push(error);
+        }
+    }
+
+    /// Shows the work for a variable: its formula, its formula with values
+    /// substituted, and its value.
+    fn show_work(&mut self, name: &String) -> Expression {
+        let e = match self.lookup(name) {
+            Some (e) => e,
+            None => {
+                Error::UndeclaredVariable (name).warn();
+                return Expression::Null;
+            },
+        };
+
+        let formula = self.simplify(&e, Resolution::NoResolve);
+        let substituted = self.substitute(&formula);
+        let value = self.simplify(&formula, Resolution::Numeric);
+
+        Expression::Work {
+            left: name.to_owned(),
+            formula: Box::new(formula),
+            substituted: Box::new(substituted),
+            value: Box::new(value),
+        }
+    }
+
+    /// Substitutes the values of the variables in a formula
+    /// without evaluating the formula.
+    fn substitute(&mut self, expr: &Expression) -> Expression {
+        use Expression::*;
+
+        match expr {
+            Identifier (_) => self.simplify(expr, Resolution::Numeric),
+            BinOp {
+                left,
+                oper,
+                right,
+            } => BinOp {
+                left: Box::new(self.substitute(left)),
+                oper: *oper,
+                right: Box::new(self.substitute(right)),
+            },
+            FnCall {
+                name,
+                arguments,
+            } => FnCall {
+                name: name.to_owned(),
+                arguments: arguments.iter()
+                    .map(|a| Substituted (Box::new(self.substitute(a))))
+                    .collect::<Vec<Expression>>(),
+            },
+            NamedArgument {
+                name,
+                value,
+            } => NamedArgument {
+                name: name.to_owned(),
+                value: Box::new(self.substitute(value)),
+            },
+            Vector (v) => Vector (v.iter()
+                .map(|e| self.substitute(e))
+                .collect::<Vec<Expression>>()
+            ),
+            e => e.to_owned(),
+        }
+    }
+
+    /// Evaluates a statement, showing the work for results and assignments
+    /// in show-work mode.
+    fn statement(&mut self, expr: &Expression) -> Expression {
+        match expr {
+            Expression::Identifier (s) if self.work && self.lookup(s).is_some() => self.show_work(s),
+            Expression::Assignment { left, right, .. } if self.work && !right.is_numeric() => {
+                self.simplify(expr, Resolution::Numeric);
+                self.show_work(left)
+            },
+            _ => self.simplify(expr, Resolution::Numeric),
+        }
+    }
+
     /// Simplifies an expression in this environment.
     fn simplify(&mut self, expr: &Expression, resolve_names: Resolution) -> Expression {
         use Expression::*;
@@ -47,20 +309,44 @@ impl Environment {
             Assignment {
                 left,
                 right,
+                annotation,
+                latex,
+                line,
             } => {
                 // Simplify the RHS
                 let sr = self.simplify(right, NoResolve);
-                
-                self.register(&left, &sr);
+                let value = self.store(left, &sr);
+
+                match annotation {
+                    Some (a) => self.annotations.insert(left.to_owned(), a.to_owned()),
+                    None => self.annotations.remove(left),
+                };
+
+                if let Some (Annotation { text, dimensions: None }) = annotation {
+                    let error = Error::InvalidAnnotation (line.to_string(), text.to_owned());
+                    error.report();
+                    self.errors.push(error);
+                }
+
+                match latex {
+                    Some (l) => self.display.names.insert(left.to_owned(), l.to_owned()),
+                    None => self.display.names.remove(left),
+                };
+
+                self.check_annotation(left, &value, *line);
                 
                 Assignment {
                     left: left.to_string(),
                     right: Box::new(sr.to_owned()),
+                    annotation: annotation.to_owned(),
+                    latex: latex.to_owned(),
+                    line: *line,
                 }
             },
             Reassignment {
                 left,
                 right,
+                line,
             } => {
                 // Make sure this variable exists
                 match self.lookup(&left) {
@@ -73,23 +359,28 @@ impl Environment {
 
                 // Simplify the RHS
                 let sr = self.simplify(right, NoResolve);
-                
-                self.register(&left, &sr);
+                let value = self.store(left, &sr);
+
+                self.check_annotation(left, &value, *line);
                 
                 Reassignment {
                     left: left.to_string(),
                     right: Box::new(sr.to_owned()),
+                    line: *line,
                 }
             },
-            Float {
-                value: _,
-                kg: _,
-                m: _,
-                s: _,
-                a: _,
-                k: _,
-                mol: _,
-            } => expr.to_owned(),
+            Float { .. } => {
+                let value = self.normalize(expr.to_owned());
+
+                // Numbers too large or too precise to be exact remain approximations
+                if let Float { value: v, .. } = &value {
+                    if self.exact && !v.is_exact() {
+                        Error::InexactNumber (v).warn();
+                    }
+                }
+
+                value
+            },
             Identifier (s) => match resolve_names {
                 NoResolve => expr.to_owned(),
                 SymbolsOnly => match self.lookup(&s) {
@@ -101,7 +392,9 @@ impl Environment {
                 },
                 Numeric => match self.lookup(&s) {
                     Some (e) => self.simplify(&e, Numeric),
-                    None => {
+                    None => if let Some (u) = unit_value(s, &self.display.units) {
+                        self.normalize(u)
+                    } else {
                         Error::UndeclaredVariable (&s).warn();
                         Null
                     },
@@ -111,16 +404,20 @@ impl Environment {
                 Some (e) => Reassignment {
                     left: s.to_string(),
                     right: Box::new(self.simplify(&e, NoResolve)),
+                    line: 0,
                 },
                 None => {
                     Error::UndeclaredVariable (&s).warn();
                     Null
                 },
             },
+            ShowWork (s) => self.show_work(s),
+            Work { .. } | Substituted (_) => expr.to_owned(),
             FullSymbolic (s) => match self.lookup(&s) {
                 Some (e) => Reassignment {
                     left: s.to_string(),
                     right: Box::new(self.simplify(&e, SymbolsOnly)),
+                    line: 0,
                 },
                 None => {
                     Error::UndeclaredVariable (&s).warn();
@@ -140,13 +437,30 @@ impl Environment {
                 name,
                 arguments,
             } => match resolve_names {
-                Numeric => carlo_std::call(
-                    name,
-                    &arguments
+                Numeric if name == "csv" => {
+                    // Data files are found relative to the source file
+                    let arguments = carlo_std::locate_csv(arguments, self.directory.as_deref());
+
+                    match carlo_std::read_csv(&arguments, &self.display.units) {
+                        Ok (t) => self.normalize(t),
+                        Err (e) => {
+                            e.report();
+                            self.errors.push(e);
+                            Null
+                        },
+                    }
+                },
+                Numeric if carlo_std::UNEVALUATED.contains(&name.as_str()) => {
+                    self.normalize(carlo_std::call(name, arguments, &self.display.units))
+                },
+                Numeric => {
+                    let arguments = arguments
                         .iter()
                         .map(|a| self.simplify(a, Numeric))
-                        .collect::<Vec<Expression>>()
-                ),
+                        .collect::<Vec<Expression>>();
+
+                    self.normalize(carlo_std::call(name, &arguments, &self.display.units))
+                },
                 SymbolsOnly => FnCall {
                     name: name.to_owned(),
                     arguments: arguments
@@ -154,56 +468,336 @@ impl Environment {
                         .map(|a| self.simplify(a, SymbolsOnly))
                         .collect::<Vec<Expression>>()
                 },
-                NoResolve => expr.to_owned(),
+                NoResolve => FnCall {
+                    name: name.to_owned(),
+                    arguments: arguments
+                        .iter()
+                        .map(|a| self.simplify(a, NoResolve))
+                        .collect::<Vec<Expression>>()
+                },
             }
+            NamedArgument {
+                name,
+                value,
+            } => NamedArgument {
+                name: name.to_owned(),
+                value: Box::new(self.simplify(value, resolve_names)),
+            },
+            Vector (v) => Vector (v.iter()
+                .map(|e| self.simplify(e, resolve_names))
+                .collect::<Vec<Expression>>()
+            ),
+            Formatted {
+                value,
+                notation,
+            } => Formatted {
+                value: Box::new(self.simplify(value, resolve_names)),
+                notation: *notation,
+            },
+            Ratio {
+                value,
+                unit,
+            } => Ratio {
+                value: Box::new(self.simplify(value, resolve_names)),
+                unit: unit.to_owned(),
+            },
+            Text (_) => expr.to_owned(),
+            Table (_) => expr.to_owned(),
             Header (_) => expr.to_owned(),
             Subheader (_) => expr.to_owned(),
             Subsubheader (_) => expr.to_owned(),
             Paragraph (_) => expr.to_owned(),
+            Pragma {
+                name,
+                arguments,
+            } => {
+                self.pragma(name, arguments);
+                Null
+            },
+            UnitDeclaration {
+                name,
+                value,
+            } => {
+                match value {
+                    Some (v) => {
+                        self.display.units.define(name, *v.to_owned());
+
+                        // The definition is displayed like any other value
+                        UnitDeclaration {
+                            name: name.to_owned(),
+                            value: Some (Box::new(self.normalize(*v.to_owned()))),
+                        }
+                    },
+                    None => {
+                        self.display.units.define_base(name);
+                        expr.to_owned()
+                    },
+                }
+            },
+            Dimension (d) => {
+                self.display.units.declare_dimension(d);
+                expr.to_owned()
+            },
             Null => Null,
         }
     }
 
-    /// Evaluates a series of statements in this environment.
-    pub fn evaluate(&mut self, expressions: &Vec<Expression>) -> String {
+    /// Evaluates a series of statements in this environment, rendering the result
+    /// of each statement with the provided function (e.g. as text or LaTeX).
+    fn document(
+        &mut self,
+        expressions: &Vec<Expression>,
+        mut render: impl FnMut(&Expression, &DisplaySettings) -> String,
+    ) -> String {
         let mut output = String::new();
 
         for expr in expressions {
-            let out = self.simplify(expr, Resolution::Numeric);
+            let out = self.statement(expr);
 
-            if let Expression::Null = expr {
-                // Do not print Null
+            if let Expression::Null
+                | Expression::Pragma { .. }
+                | Expression::Dimension (_)
+                | Expression::UnitDeclaration { value: None, .. } = expr
+            {
+                // Do not print Null, pragmas, or dimension declarations
             } else {
-                output.push_str(&format!("{}\n", out));
+                output.push_str(&render(&out, &self.display));
             }
         }
 
         output
     }
 
+    /// Evaluates a series of statements in this environment.
+    pub fn evaluate(&mut self, expressions: &Vec<Expression>) -> String {
+        self.document(expressions, |out, display| format!("{}\n", out.format(display)))
+    }
+
     /// Evaluates a series of statements in this environment and returns LaTeX.
     pub fn latex_evaluate(&mut self, expressions: &Vec<Expression>) -> String {
+        self.document(expressions, |out, display| {
+            let latex = out.latex(true, display);
+            if !latex.contains("\\section")
+                && !latex.contains("\\begin{align")
+                && !latex.contains("\\subsection") 
+                && !latex.contains("\\subsubsection")
+                && !latex.contains("\\par")
+            {
+                format!("$$\n{}\n$$\n", latex)
+            } else {
+                format!("\n{}\n\n", latex)
+            }
+        })
+    }
+
+    /// Evaluates a series of statements, each with the line on which it begins,
+    /// in this environment and returns LaTeX with the source line of each of its lines.
+    pub fn latex_evaluate_located(&mut self, statements: &[(usize, Expression)]) -> (String, Vec<usize>) {
         let mut output = String::new();
+        let mut lines = Vec::new();
 
-        for expr in expressions {
-            let out = self.simplify(expr, Resolution::Numeric);
+        for (line, expr) in statements {
+            let latex = self.latex_evaluate(&vec![expr.to_owned()]);
 
-            if let Expression::Null = expr {
-                // Do not print Null
-            } else {
-                let latex = out.latex(true);
-                if !latex.contains("\\section")
-                    && !latex.contains("\\subsection") 
-                    && !latex.contains("\\subsubsection")
-                    && !latex.contains("\\par")
-                {
-                    output.push_str(&format!("$$\n{}\n$$\n", out.latex(true)));
-                } else {
-                    output.push_str(&format!("\n{}\n\n", out.latex(true)));
-                }
-            }
+            lines.extend(std::iter::repeat_n(*line, latex.matches('\n').count()));
+            output.push_str(&latex);
         }
 
-        output
+        (output, lines)
+    }
+
+    /// Evaluates a series of statements in this environment and returns Markdown,
+    /// with headers and paragraphs as text and all math in `$$` blocks.
+    pub fn markdown_evaluate(&mut self, expressions: &Vec<Expression>) -> String {
+        use Expression::*;
+
+        self.document(expressions, |out, display| match out {
+            Header (s) => format!("# {}\n\n", escape_markdown(s)),
+            Subheader (s) => format!("## {}\n\n", escape_markdown(s)),
+            Subsubheader (s) => format!("### {}\n\n", escape_markdown(s)),
+            Paragraph (s) => format!("{}\n\n", escape_markdown(s.trim())),
+            _ => {
+                // Web forges render `aligned` but not `align*` in math blocks
+                let latex = out.latex(true, display)
+                    .replace("\\begin{align*}", "\\begin{aligned}")
+                    .replace("\\end{align*}", "\\end{aligned}");
+
+                format!("$$\n{}\n$$\n\n", latex)
+            },
+        })
+    }
+
+    /// Evaluates a series of statements in this environment and returns Typst,
+    /// with headers and paragraphs as markup and all math in display equations.
+    pub fn typst_evaluate(&mut self, expressions: &Vec<Expression>) -> String {
+        use Expression::*;
+
+        self.document(expressions, |out, display| match out {
+            Header (_) | Subheader (_) | Subsubheader (_) | Paragraph (_) => format!("{}\n\n", out.typst(true, display)),
+            _ => format!("$ {} $\n\n", out.typst(true, display)),
+        })
     }
-}
\ No newline at end of file
+}
+
+/// Characters of text that have a meaning in Markdown wherever they appear
+const MARKDOWN: [char; 12] = ['\\', '`', '*', '_', '[', ']', '<', '>', '#', '|', '~', '$'];
+
+/// Escapes the characters of text that have a meaning in Markdown,
+/// including a list marker at the start of the text (e.g. `- ` or `1. `).
+fn escape_markdown(text: &str) -> String {
+    let mut output = String::new();
+
+    for c in text.chars() {
+        if MARKDOWN.contains(&c) {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+
+    let digits = output.chars().take_while(|c| c.is_ascii_digit()).count();
+
+    if output.starts_with(['-', '+']) {
+        output.insert(0, '\\');
+    } else if digits > 0 && output[digits..].starts_with(['.', ')']) {
+        output.insert(digits, '\\');
+    }
+
+    output
+}
+
+#[test]
+fn test_show_work() {
+    let parser = crate::Parser::new(false);
+    let mut env = Environment::new();
+
+    let output = env.evaluate(&parser.parse("let a = 10 kg\nlet b = 2 m\nlet c = a * b\n?c\n?a\n#pragma work\nlet d = c / b\n"));
+
+    assert_eq!(output.lines().collect::<Vec<&str>>()[3..], [
+        "c = (a * b) = (10.0000 kg * 2.0000 m) = 20.0000 kg m",
+        "a = 10.0000 kg",
+        "d = (c / b) = (20.0000 kg m / 2.0000 m) = 10.0000 kg",
+    ]);
+
+    let latex = env.latex_evaluate(&parser.parse("?c\n"));
+    assert!(latex.contains("c &= a b \\\\\n&= \\left(10.0000 \\; \\mathrm{kg}\\right) \\left(2.0000 \\; \\mathrm{m}\\right) \\\\\n&= 20.0000"));
+}
+
+#[test]
+fn test_markdown() {
+    let parser = crate::Parser::new(false);
+    let mut env = Environment::new();
+
+    let markdown = env.markdown_evaluate(&parser.parse("@ Loads\n~ Dead load on the beam.\n#pragma work\nlet w = 2 kN / 1 m\n"));
+
+    assert!(markdown.starts_with("# Loads\n\nDead load on the beam.\n\n$$\n\\begin{aligned}\nw &= "));
+    assert!(markdown.ends_with("\\end{aligned}\n$$\n\n"));
+
+    // Text is escaped
+    let markdown = env.markdown_evaluate(&parser.parse("@ Loads_1 *dead*\n~ - 2. [see] <b> $x$\n~ 2. Case\n"));
+    assert_eq!(markdown, "# Loads\\_1 \\*dead\\*\n\n\\- 2. \\[see\\] \\<b\\> \\$x\\$\n\n2\\. Case\n\n");
+}
+
+#[test]
+fn test_latex_lines() {
+    let parser = crate::Parser::new(false);
+    let mut env = Environment::new();
+
+    let (latex, lines) = env.latex_evaluate_located(&parser.parse_located("@ Loads\n\nlet w = 2\n#pragma work\nlet v = w * 2\n"));
+
+    let source = |text: &str| latex.lines()
+        .position(|l| l.contains(text))
+        .map(|i| lines[i]);
+
+    assert_eq!(latex.lines().count(), lines.len());
+    assert_eq!(source("\\section{Loads}"), Some (1));
+    assert_eq!(source("w :="), Some (3));
+    assert_eq!(source("v &="), Some (5));
+}
+
+#[test]
+fn test_tables() {
+    let directory = std::env::temp_dir().join("carlo_test_tables");
+    let _ = std::fs::create_dir_all(&directory);
+    std::fs::write(directory.join("pull.csv"), "time [s], force [kN]\n0.0, 1.5\n0.5, 2.0\n").unwrap();
+
+    let parser = crate::Parser::new(false);
+    let mut env = Environment::new();
+    env.set_source(&directory.join("pull.cl"));
+
+    // Tables are read once, relative to the source file
+    env.evaluate(&parser.parse("let data = csv(\"pull.csv\")\n"));
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    let output = env.evaluate(&parser.parse("column(data, \"force\")\nlet data_force = 2\ndata_force\n"));
+    assert_eq!(output, "[1500.0000 N, 2000.0000 N]\ndata_force = 2.0000\n2.0000\n");
+}
+
+#[test]
+fn test_unit_declarations() {
+    let parser = crate::Parser::new(false);
+    let mut env = Environment::new();
+
+    // Definitions are displayed like other values, and failed declarations are not units
+    let output = env.evaluate(&parser.parse("unit kipf = 1000 lbf\nunit bad = 3 zzz\nlet x = 2 kipf\nlet y = 2 bad\n"));
+    assert_eq!(output.lines().collect::<Vec<&str>>()[..3], [
+        "1 kipf = 4448.2216 N",
+        "x = 8896.4432 N",
+        "y = 2.0000",
+    ]);
+
+    // Units of declared dimensions
+    let output = env.evaluate(&parser.parse("dimension currency\nunit USD\nunit EUR = 1.1 USD\nlet price = 3 EUR\nprice / 2 s\n"));
+    assert_eq!(output, "1 EUR = 1.1000 USD\nprice = 3.3000 USD\n1.6500 USD s^-1\n");
+}
+
+#[test]
+fn test_annotation_errors() {
+    let parser = crate::Parser::new(false);
+    let mut env = Environment::new();
+
+    env.evaluate(&parser.parse("let L: Length = 2 m\n\nlet T: Time = L\nL = 3 s\nlet v: [length/time] = 3 s\nlet F: Force / [length] = 2 N/m\n"));
+
+    let errors = env.errors().iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>();
+
+    assert_eq!(errors, [
+        "Mismatched dimensions at line 3: T is annotated as Time but has dimensions of Length",
+        "Mismatched dimensions at line 4: L is annotated as Length but has dimensions of Time",
+        "Invalid annotation at line 5: [length/time]",
+    ]);
+}
+
+#[test]
+fn test_data_file_errors() {
+    let directory = std::env::temp_dir().join("carlo_test_data_file_errors");
+    let _ = std::fs::create_dir_all(&directory);
+    std::fs::write(directory.join("bad.csv"), "time [s], force [kN]\n0.0, 1.5\n0.5\n").unwrap();
+
+    let parser = crate::Parser::new(false);
+    let source = "let a = csv(\"missing.csv\")\nlet b = csv(\"bad.csv\")\n";
+
+    let mut env = Environment::new();
+    env.set_source(&directory.join("data.cl"));
+    env.evaluate(&parser.parse(source));
+
+    let mut checker = crate::Checker::new();
+    checker.set_source(&directory.join("data.cl"));
+    checker.check(&parser.parse_located(source));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    let expected = [
+        format!("Could not read file: {}", directory.join("missing.csv").display()),
+        format!("Malformed row at {}:3: expected 2 fields but found 1", directory.join("bad.csv").display()),
+    ];
+
+    // Both evaluating and checking fail with the file and line
+    for errors in [env.errors(), checker.errors()] {
+        let errors = errors.iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(errors, expected);
+    }
+}
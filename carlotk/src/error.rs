@@ -18,18 +18,33 @@ pub enum Error<T: fmt::Display> {
     /// Could not recognize argument
     UnrecognizedArgument (T),
 
+    /// Option without a value
+    MissingOptionValue (T),
+
+    /// Invalid option value
+    InvalidOptionValue (T, T),
+
     /// Could not find file
     CouldNotFindFile (T),
 
     /// Could not read file
     CouldNotReadFile (T),
 
+    /// Could not write file
+    CouldNotWriteFile (T),
+
     /// No input file
     NoInputFile,
 
     /// Could not parse number
     CouldNotParseNumber (T),
 
+    /// Exact number could not be represented exactly
+    InexactNumber (T),
+
+    /// Division by zero
+    DivisionByZero (T),
+
     /// Could not parse expression
     CouldNotParse (T),
 
@@ -56,6 +71,69 @@ pub enum Error<T: fmt::Display> {
 
     /// Unmatched units
     UnmatchedUnits (T, T, T),
+
+    /// Unmatched vector lengths
+    UnmatchedLengths (T, T),
+
+    /// Malformed row in a data file
+    MalformedRow (T, T, T),
+
+    /// Could not recognize unit
+    UnrecognizedUnit (T),
+
+    /// Invalid function argument
+    InvalidArgument (T, T),
+
+    /// Could not recognize pragma
+    UnrecognizedPragma (T),
+
+    /// Invalid pragma argument
+    InvalidPragma (T, T),
+
+    /// Unit after a number has the name of a variable
+    ShadowedUnit (T, T),
+
+    /// Invalid unit or dimension declaration
+    InvalidDeclaration (T, T),
+
+    /// Invalid dimension annotation
+    InvalidAnnotation (T, T),
+
+    /// Value does not have the annotated dimensions
+    MismatchedDimensions (T, T, T, T),
+
+    /// Addition or subtraction of values with different dimensions
+    MismatchedOperands (T, T, T, T),
+
+    /// Elements of a vector with different dimensions
+    MismatchedElements (T, T, T),
+
+    /// Function arguments that must share dimensions have different dimensions
+    MismatchedArguments (T, T, T, T),
+
+    /// Static dimensional analysis found errors
+    FailedCheck (T),
+
+    /// Function argument must be dimensionless
+    NotDimensionless (T, T),
+
+    /// Function argument must be dimensionless (found by static analysis)
+    MismatchedArgument (T, T, T),
+
+    /// Front-matter line is not a `key: value` pair
+    InvalidFrontMatter (T, T),
+
+    /// Invalid LaTeX template
+    InvalidTemplate (T, T),
+
+    /// Could not run a LaTeX engine
+    CouldNotRunEngine (T),
+
+    /// Error reported by a LaTeX engine
+    LatexError (T, T),
+
+    /// LaTeX engine could not build a document
+    FailedBuild (T, T),
 }
 
 /// Converts an error into a string.
@@ -67,10 +145,15 @@ impl<T: fmt::Display> fmt::Display for Error<T> {
             UnrecognizedSubcommand (s) => format!("Did not recognize subcommand: {}", s),
             UnrecognizedFlag (s) => format!("Did not recognize flag: {}", s),
             UnrecognizedArgument (s) => format!("Did not recognize argument: {}", s),
+            MissingOptionValue (s) => format!("Expected a value after option: {}", s),
+            InvalidOptionValue (o, s) => format!("Invalid value for option {}: {}", o, s),
             CouldNotFindFile (s) => format!("Could not locate file: {}", s),
             CouldNotReadFile (s) => format!("Could not read file: {}", s),
+            CouldNotWriteFile (s) => format!("Could not write file: {}", s),
             NoInputFile => format!("No input file provided"),
             CouldNotParseNumber (s) => format!("Could not parse number: {}", s),
+            InexactNumber (s) => format!("Could not compute {} exactly; using an approximation", s),
+            DivisionByZero (s) => format!("Division by zero: {}", s),
             CouldNotParse (s) => format!("Could not parse near token ({})", s),
             UnexpectedEOF (s) => format!("Unexpected EOF near token ({})", s),
             Expected (x, a) => format!("Expected token of class ({}) but instead found token of class ({})", x, a),
@@ -80,6 +163,27 @@ impl<T: fmt::Display> fmt::Display for Error<T> {
             CouldNotFlushStdout (i) => format!("Could not flust stdout near In[{}]", i),
             UndeclaredVariable (s) => format!("Found undeclared variable: {}", s),
             UnmatchedUnits (u, l, r) => format!("Unmatched unit powers ({}^{}) and ({}^{})", u, l, u, r),
+            UnmatchedLengths (l, r) => format!("Unmatched vector lengths ({}) and ({})", l, r),
+            MalformedRow (file, line, reason) => format!("Malformed row at {}:{}: {}", file, line, reason),
+            UnrecognizedUnit (s) => format!("Did not recognize unit: {}", s),
+            InvalidArgument (f, s) => format!("Invalid argument to function {}: {}", f, s),
+            UnrecognizedPragma (s) => format!("Did not recognize pragma: {}", s),
+            InvalidPragma (p, s) => format!("Invalid argument to pragma {}: {}", p, s),
+            ShadowedUnit (l, s) => format!("Ambiguous unit at line {}: {} after a number is the unit, not the variable of the same name", l, s),
+            InvalidDeclaration (n, s) => format!("Invalid declaration of {}: {}", n, s),
+            InvalidAnnotation (l, s) => format!("Invalid annotation at line {}: {}", l, s),
+            MismatchedDimensions (l, v, x, a) => format!("Mismatched dimensions at line {}: {} is annotated as {} but has dimensions of {}", l, v, x, a),
+            MismatchedOperands (l, o, x, y) => format!("Mismatched dimensions at line {}: cannot {} {} and {}", l, o, x, y),
+            MismatchedElements (l, x, y) => format!("Mismatched dimensions at line {}: vector has elements with dimensions of {} and {}", l, x, y),
+            MismatchedArguments (l, f, x, y) => format!("Mismatched dimensions at line {}: arguments to function {} have dimensions of {} and {}", l, f, x, y),
+            NotDimensionless (f, d) => format!("Argument to function {} must be dimensionless but has dimensions of {}", f, d),
+            MismatchedArgument (l, f, d) => format!("Mismatched dimensions at line {}: argument to function {} must be dimensionless but has dimensions of {}", l, f, d),
+            InvalidFrontMatter (l, s) => format!("Invalid front matter at line {}: {}", l, s),
+            InvalidTemplate (t, s) => format!("Invalid template {}: {}", t, s),
+            CouldNotRunEngine (e) => format!("Could not run LaTeX engine {}; is it installed?", e),
+            LatexError (l, s) => format!("LaTeX error at {}: {}", l, s),
+            FailedBuild (e, l) => format!("LaTeX engine {} could not build the document; see {}", e, l),
+            FailedCheck (n) => format!("Static analysis found {} error(s); use --no-check to run anyway", n),
         };
 
         write!(f, "{}", string)
@@ -89,7 +193,12 @@ impl<T: fmt::Display> fmt::Display for Error<T> {
 impl<T: fmt::Display> Error<T> {
     pub fn throw(&self) -> ! {
         println!("{} {}", "(error)".truecolor(255, 60, 40).bold(), self);
-        exit(0);
+        exit(1);
+    }
+
+    /// Reports this error without exiting.
+    pub fn report(&self) {
+        println!("{} {}\n", "(error)".truecolor(255, 60, 40).bold(), self);
     }
 
     pub fn warn(&self) {
@@ -3,28 +3,56 @@
 use std::fmt;
 
 use crate::{
+    Annotation,
+    BINARY_PREFIXES,
     BinaryOperation,
     carlo_std,
-    UNITS,
+    Dimensions,
+    DisplaySettings,
+    identifier::{
+        ACCENTS,
+        Identifier,
+        Part,
+    },
+    INFORMATION,
+    INFORMATION_UNITS,
+    Notation,
+    Number,
+    PREFIXED,
+    PREFIXES,
+    UnitTable,
+    parser::unit_value,
+    siunitx,
 };
 
-/// LaTeX special identifiers
-const GREEK: [&str; 13] = [
-    "alpha",
-    "beta",
-    "gamma",
-    "delta",
-    "epsilon",
-    "varepsilon",
-    "xi",
-    "pi",
-    "theta",
-    "phi",
-    "pi",
-    "psi",
-    "omega",    
+/// Characters of text that have a meaning in LaTeX, with their escaped forms
+const LATEX_SPECIAL: [(char, &str); 10] = [
+    ('&', "\\&"),
+    ('%', "\\%"),
+    ('$', "\\$"),
+    ('#', "\\#"),
+    ('_', "\\_"),
+    ('{', "\\{"),
+    ('}', "\\}"),
+    ('~', "\\textasciitilde{}"),
+    ('^', "\\textasciicircum{}"),
+    ('\\', "\\textbackslash{}"),
 ];
 
+/// Escapes the characters of text that have a meaning in LaTeX.
+pub fn escape_latex(text: &str) -> String {
+    let mut output = String::new();
+
+    for c in text.chars() {
+        match LATEX_SPECIAL.iter().find(|(s, _)| *s == c) {
+            Some ((_, e)) => output.push_str(e),
+            None => output.push(c),
+        }
+    }
+
+    output
+}
+
 #[derive(Clone, Debug)]
 /// Enumerates the expression available to the Carlo parser.
 pub enum Expression {
@@ -32,23 +60,38 @@ pub enum Expression {
     Assignment {
         left: String,
         right: Box<Expression>,
+
+        /// Dimension annotation (e.g. `Pressure`)
+        annotation: Option<Annotation>,
+
+        /// LaTeX written for this variable in place of its name (e.g. `\sigma_{\max}`)
+        latex: Option<String>,
+
+        /// Line of the input on which this assignment begins
+        line: usize,
     },
 
     /// Reassignment
     Reassignment {
         left: String,
         right: Box<Expression>,
+
+        /// Line of the input on which this reassignment begins
+        /// (or 0 if it is a result rather than a statement)
+        line: usize,
     },
 
-    /// 64-bit dimensioned floating-point
+    /// Dimensioned number (exact or floating-point)
     Float {
-        value: f64,
+        value: Number,
         kg: f64,
         m: f64,
         s: f64,
         a: f64,
         k: f64,
         mol: f64,
+        cd: f64,
+        other: Dimensions,
     },
 
     /// Identifier
@@ -60,6 +103,21 @@ pub enum Expression {
     /// Full-resolution symbolic identifier
     FullSymbolic (String),
 
+    /// Show-work identifier
+    ShowWork (String),
+
+    /// Worked result: a variable with its formula, its formula with values
+    /// substituted, and its value (e.g. `F = m g = (10 kg) (9.81 m s^-2) = 98.1 N`)
+    Work {
+        left: String,
+        formula: Box<Expression>,
+        substituted: Box<Expression>,
+        value: Box<Expression>,
+    },
+
+    /// Argument of a function with values substituted for its variables
+    Substituted (Box<Expression>),
+
     /// Binary operation
     BinOp {
         left: Box<Expression>,
@@ -73,6 +131,33 @@ pub enum Expression {
         arguments: Vec<Expression>,
     },
 
+    /// Named function argument
+    NamedArgument {
+        name: String,
+        value: Box<Expression>,
+    },
+
+    /// Text (string literal)
+    Text (String),
+
+    /// Vector
+    Vector (Vec<Expression>),
+
+    /// Table of named columns
+    Table (Vec<(String, Expression)>),
+
+    /// Value with its own numeric notation
+    Formatted {
+        value: Box<Expression>,
+        notation: Notation,
+    },
+
+    /// Dimensionless value displayed in a dimensionless unit (e.g. `5 %` or `2 mm/mm`)
+    Ratio {
+        value: Box<Expression>,
+        unit: String,
+    },
+
     /// Subsubheader
     Subsubheader (String),
 
@@ -85,38 +170,110 @@ pub enum Expression {
     /// Paragraph
     Paragraph (String),
 
+    /// Pragma (interpreter directive)
+    Pragma {
+        name: String,
+        arguments: Vec<String>,
+    },
+
+    /// Unit declaration (without a value for the base unit of a dimension)
+    UnitDeclaration {
+        name: String,
+        value: Option<Box<Expression>>,
+    },
+
+    /// Dimension declaration
+    Dimension (String),
+
     /// Null
     Null,
 }
 
 impl Expression {
-    pub fn is_numeric(&self) -> bool {
+    /// Constructs a dimensionless number.
+    pub fn dimensionless(value: Number) -> Self {
+        Self::Float {
+            value,
+            kg: 0.0,
+            m: 0.0,
+            s: 0.0,
+            a: 0.0,
+            k: 0.0,
+            mol: 0.0,
+            cd: 0.0,
+            other: Dimensions::new(),
+        }
+    }
+
+    /// Checks if this expression is a dimensionless number.
+    pub fn is_dimensionless(&self) -> bool {
         match self {
             Self::Float {
                 value: _,
-                kg: _,
-                m: _,
-                s: _,
-                a: _,
-                k: _,
-                mol: _,
-            } => true,
+                kg,
+                m,
+                s,
+                a,
+                k,
+                mol,
+                cd,
+                other,
+            } => [kg, m, s, a, k, mol, cd].iter().all(|p| **p == 0.0) && other.is_empty(),
             _ => false,
         }
     }
 
-    pub fn latex(&self, toplevel: bool) -> String {
+    /// Checks if this expression refers to any variables.
+    pub fn has_identifiers(&self) -> bool {
+        use Expression::*;
+
+        match self {
+            Identifier (_) => true,
+            BinOp { left, right, .. } => left.has_identifiers() || right.has_identifiers(),
+            FnCall { arguments, .. } => arguments.iter().any(|a| a.has_identifiers()),
+            NamedArgument { value, .. } | Formatted { value, .. } | Ratio { value, .. } => value.has_identifiers(),
+            Vector (v) => v.iter().any(|e| e.has_identifiers()),
+            _ => false,
+        }
+    }
+
+    /// Checks if two numbers have the same dimensions.
+    pub fn same_dimensions(&self, other: &Expression) -> bool {
+        match (self, other) {
+            (
+                Self::Float { kg, m, s, a, k, mol, cd, other, .. },
+                Self::Float { kg: r_kg, m: r_m, s: r_s, a: r_a, k: r_k, mol: r_mol, cd: r_cd, other: r_other, .. },
+            ) => (kg, m, s, a, k, mol, cd, other) == (r_kg, r_m, r_s, r_a, r_k, r_mol, r_cd, r_other),
+            _ => false,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        match self {
+            Self::Float { .. } => true,
+            Self::Vector (v) => v.iter().all(|e| e.is_numeric()),
+            Self::Table (t) => t.iter().all(|(_, c)| c.is_numeric()),
+            Self::Formatted { value, .. } => value.is_numeric(),
+            Self::Ratio { value, .. } => value.is_numeric(),
+            _ => false,
+        }
+    }
+
+    /// Converts this expression to LaTeX.
+    pub fn latex(&self, toplevel: bool, settings: &DisplaySettings) -> String {
         use Expression::*;
 
         let string = match self {
             Assignment {
                 left,
                 right,
-            } => format!("{} := {}", latex_identifier(left), right.latex(true)),
+                ..
+            } => format!("{} := {}", latex_identifier(left, settings), right.latex(true, settings)),
             Reassignment {
                 left,
                 right,
-            } => format!("{} = {}", latex_identifier(left), right.latex(true)),
+                ..
+            } => format!("{} = {}", latex_identifier(left, settings), right.latex(true, settings)),
             Float {
                 value,
                 kg,
@@ -125,24 +282,93 @@ impl Expression {
                 a,
                 k,
                 mol,
-            } => latex_unit(*value, *kg, *m, *s, *a, *k, *mol),
-            Identifier (s) => latex_identifier(s),
+                cd,
+                other,
+            } => latex_unit(value.to_owned(), (*kg, *m, *s, *a, *k, *mol, *cd), other, settings),
+            Identifier (s) => latex_identifier(s, settings),
             Symbolic (s) => format!("{}", s),
             FullSymbolic (s) => format!("{}", s),
+            ShowWork (s) => s.to_string(),
+            Substituted (e) => latex_substituted(e, toplevel, settings),
+            Work {
+                left,
+                formula,
+                substituted,
+                value,
+            } => {
+                let rows = work_rows(formula, [
+                    formula.latex(true, settings),
+                    latex_substituted(substituted, true, settings),
+                    value.latex(true, settings),
+                ]);
+
+                format!(
+                    "\\begin{{align*}}\n{} &= {}\n\\end{{align*}}",
+                    latex_identifier(left, settings),
+                    rows.join(" \\\\\n&= "),
+                )
+            },
+            BinOp {
+                left,
+                oper: BinaryOperation::Div,
+                right,
+            } => format!("\\frac{{{}}}{{{}}}", left.latex(true, settings), right.latex(true, settings)),
             BinOp {
                 left,
                 oper,
                 right,
-            } => if toplevel {
-                format!("{}{}{}", left.latex(false), oper.latex(), right.latex(false))
-            } else {
-                format!("({}{}{})", left.latex(false), oper.latex(), right.latex(false))
+            } => {
+                let output = latex_operation(left, *oper, right, &|e, toplevel| e.latex(toplevel, settings));
+
+                if toplevel {
+                    output
+                } else {
+                    format!("\\left({}\\right)", output)
+                }
             },
             FnCall {
                 name,
                 arguments,
-            } => carlo_std::latex(name, arguments),
+            } => carlo_std::latex(name, arguments, settings),
+            NamedArgument {
+                name,
+                value,
+            } => format!("\\mathrm{{{}}} = {}", name, value.latex(true, settings)),
+            Text (s) => format!("\\text{{``{}''}}", s),
+            Vector (v) => format!(
+                "\\left[ {} \\right]",
+                v.iter()
+                    .map(|e| e.latex(true, settings))
+                    .collect::<Vec<String>>()
+                    .join(", \\; "),
+            ),
+            Table (t) => latex_table(t, settings),
+            Formatted {
+                value,
+                notation,
+            } => value.latex(toplevel, &settings.with_notation(*notation)),
+            Ratio {
+                value,
+                unit,
+            } => match ratio(value, unit, settings) {
+                Some (n) if settings.siunitx => siunitx::ratio(&settings.latex_number(&n), unit)
+                    .unwrap_or_else(|| format!("{} \\; \\mathrm{{{}}}", settings.latex_number(&n), latex_ratio_unit(unit))),
+                Some (n) => format!("{} \\; \\mathrm{{{}}}", settings.latex_number(&n), latex_ratio_unit(unit)),
+                None => value.latex(toplevel, settings),
+            },
             Paragraph (s) => format!("\n{}\\par\n", s),
+            Pragma {
+                name: _,
+                arguments: _,
+            } => String::new(),
+            UnitDeclaration {
+                name,
+                value,
+            } => match value {
+                Some (v) => format!("1 \\; \\mathrm{{{}}} = {}", name, v.latex(true, settings)),
+                None => format!("\\mathrm{{{}}}", name),
+            },
+            Dimension (s) => format!("\\text{{{}}}", s),
             Header (s) => format!("\n\\section{{{}}}\n", s),
             Subheader (s) => format!("\n\\subsection{{{}}}\n", s),
             Subsubheader (s) => format!("\n\\subsubsection{{{}}}\n", s),
@@ -153,109 +379,311 @@ impl Expression {
     }
 }
 
-fn format_unit(
-    mut value: f64,
-    mut kg: f64,
-    mut m: f64,
-    mut s: f64,
-    mut a: f64,
-    mut k: f64,
-    mut mol: f64,
-) -> String {
-    let mut output = String::new();
+/// A unit in an output, with its prefix and power.
+pub(crate) type UnitPart = (Option<&'static str>, String, f64);
+
+/// Splits a value and its dimensions into a value and a list of
+/// units, each with a prefix and a power.
+pub(crate) fn unit_parts(
+    mut value: Number,
+    dimensions: (f64, f64, f64, f64, f64, f64, f64),
+    other: &Dimensions,
+    settings: &DisplaySettings,
+) -> (Number, Vec<UnitPart>) {
+    let (kg, m, s, a, k, mol, cd) = dimensions;
+    let mut parts = Vec::new();
 
-    // Iterate through units and add values
-    for (name, unit) in UNITS {
-        if (kg, m, s, a, k, mol) == (unit.1, unit.2, unit.3, unit.4, unit.5, unit.6) {
-            value /= unit.0;
+    let float = Expression::Float {
+        value: value.to_owned(),
+        kg,
+        m,
+        s,
+        a,
+        k,
+        mol,
+        cd,
+        other: other.to_owned(),
+    };
 
-            output.push_str(&format!(" {}", name));
+    // Use the units of the unit system for the SI dimensions,
+    // and base units for any declared dimensions
+    if let Some ((unit, units)) = settings.system.units(&float, &settings.units) {
+        value = value / unit;
 
-            kg -= unit.1;
-            m -= unit.2;
-            s -= unit.3;
-            a -= unit.4;
-            k -= unit.5;
-            mol -= unit.6;
+        for (unit, pow) in units {
+            parts.push((None, unit.to_string(), pow));
         }
     }
 
-    for (unit, pow) in [
-        ("kg", kg),
-        ("m", m),
-        ("s", s),
-        ("A", a),
-        ("K", k),
-        ("mol", mol),
-    ] {
+    for (dimension, pow) in other.iter() {
+        if dimension == INFORMATION && settings.bytes {
+            let (byte, bits) = INFORMATION_UNITS[1];
+            value = value / Number::exact(bits).pow(*pow);
+            parts.push((None, byte.to_string(), *pow));
+        } else {
+            parts.push((None, settings.units.base_unit(dimension), *pow));
+        }
+    }
+
+    // Units with positive powers come first (e.g. `bit s^-1`)
+    parts.sort_by_key(|(_, _, pow)| *pow < 0.0);
+
+    if settings.bytes && parts.first().is_some_and(|(_, unit, _)| unit == INFORMATION_UNITS[1].0) {
+        value = binary_prefix(value, &mut parts);
+    } else if settings.prefixes {
+        value = prefix(value, &mut parts, &settings.units);
+    }
+
+    (value, parts)
+}
+
+/// Chooses the prefix for the first unit of a list of units that brings the value
+/// into [1, 1000), returning the adjusted value.
+/// 
+/// Only the first unit is prefixed (e.g. `km s^-1`), and only if it has a positive
+/// integer power and can take a prefix.
+fn prefix(mut value: Number, parts: &mut [UnitPart], units: &UnitTable) -> Number {
+    let (pre, unit, pow) = match parts.first_mut() {
+        Some (p) => p,
+        None => return value,
+    };
+
+    // Units that already carry a prefix are prefixed from their unprefixed unit
+    if let Some ((_, base, m)) = PREFIXED.iter().find(|(n, _, _)| n == unit) {
+        if *pow > 0.0 && pow.fract() == 0.0 && value.to_f64().is_normal() && units.prefixable(base) {
+            value = value * Number::exact(*m).pow(*pow);
+            *unit = base.to_string();
+        }
+    }
+
+    let magnitude = value.to_f64().abs();
+
+    if !units.prefixable(unit)
+        || *pow <= 0.0
+        || pow.fract() != 0.0
+        || magnitude == 0.0
+        || !magnitude.is_finite()
+    {
+        return value;
+    }
+
+    // Only prefixes that are powers of 1000 are chosen automatically
+    let mut candidates = PREFIXES.iter()
+        .map(|(p, m)| (Some (*p), *m))
+        .filter(|(_, m)| (m.log10().round() as i32) % 3 == 0)
+        .chain([(None, 1.0)])
+        .collect::<Vec<(Option<&str>, f64)>>();
+    candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    // Aliases (e.g. `µ` for `u`) are never chosen
+    candidates.dedup_by(|a, b| a.1 == b.1);
+
+    // Use the largest prefix that keeps the value at least 1,
+    // or the smallest prefix if the value is very small
+    let (p, m) = candidates.iter()
+        .rev()
+        .find(|(_, m)| magnitude / m.powf(*pow) >= 1.0)
+        .unwrap_or(&candidates[0]);
+
+    *pre = *p;
+    value / Number::exact(*m).pow(*pow)
+}
+
+/// Chooses the binary prefix for bytes as the first unit of a list of units that
+/// brings the value into [1, 1024) (e.g. `1.5 MiB`), returning the adjusted value.
+fn binary_prefix(value: Number, parts: &mut [UnitPart]) -> Number {
+    let (pre, _, pow) = match parts.first_mut() {
+        Some (p) => p,
+        None => return value,
+    };
+
+    let magnitude = value.to_f64().abs();
+
+    if *pow <= 0.0 || pow.fract() != 0.0 || magnitude == 0.0 || !magnitude.is_finite() {
+        return value;
+    }
+
+    match BINARY_PREFIXES.iter().rev().find(|(_, m)| magnitude / m.powf(*pow) >= 1.0) {
+        Some ((p, m)) => {
+            *pre = Some (*p);
+            value / Number::exact(*m).pow(*pow)
+        },
+        None => value,
+    }
+}
+
+fn format_unit(
+    value: Number,
+    dimensions: (f64, f64, f64, f64, f64, f64, f64),
+    other: &Dimensions,
+    settings: &DisplaySettings,
+) -> String {
+    let (value, parts) = unit_parts(value, dimensions, other, settings);
+    let mut output = String::new();
+
+    for (prefix, unit, pow) in parts {
+        let prefix = prefix.unwrap_or_default();
+
         if pow == 1.0 {
-            output.push_str(&format!(" {}", unit));
-        } else if pow != 0.0 {
-            output.push_str(&format!(" {}^{}", unit, pow));
+            output.push_str(&format!(" {}{}", prefix, unit));
+        } else {
+            output.push_str(&format!(" {}{}^{}", prefix, unit, pow));
         }
     }
 
-    format!("{:.4}{}", value, output)   
+    format!("{}{}", settings.format_number(&value), output)
 }
 
 fn latex_unit(
-    mut value: f64,
-    mut kg: f64,
-    mut m: f64,
-    mut s: f64,
-    mut a: f64,
-    mut k: f64,
-    mut mol: f64,
+    value: Number,
+    dimensions: (f64, f64, f64, f64, f64, f64, f64),
+    other: &Dimensions,
+    settings: &DisplaySettings,
 ) -> String {
+    let (value, parts) = unit_parts(value, dimensions, other, settings);
+
+    if settings.siunitx {
+        return siunitx::quantity(&settings.latex_number(&value), &parts);
+    }
+
     let mut output = String::new();
 
-    // Iterate through units and add values
-    for (name, unit) in UNITS {
-        if (kg, m, s, a, k, mol) == (unit.1, unit.2, unit.3, unit.4, unit.5, unit.6) {
-            value /= unit.0;
+    for (prefix, unit, pow) in parts {
+        let prefix = match prefix {
+            Some ("u" | "µ" | "μ") => "\\mu ",
+            Some (p) => p,
+            None => "",
+        };
+        let unit = unit.replace(['Ω', 'Ω'], "\\Omega ");
+
+        if pow == 1.0 {
+            output.push_str(&format!(" \\; \\mathrm{{{}{}}}", prefix, unit));
+        } else {
+            output.push_str(&format!(" \\; \\mathrm{{{}{}^{{{}}}}}", prefix, unit, pow));
+        }
+    }
+
+    format!("{}{}", settings.latex_number(&value), output)
+}
+
+/// Gets the number displayed for a dimensionless value in a dimensionless unit,
+/// converting power ratios to decibels.
+pub(crate) fn ratio(value: &Expression, unit: &str, settings: &DisplaySettings) -> Option<Number> {
+    let value = match value {
+        Expression::Float { value, .. } => value.to_owned(),
+        _ => return None,
+    };
+
+    if unit == "dB" {
+        return Some (Number::from(10.0 * value.to_f64().log10()));
+    }
+
+    match unit_value(unit, &settings.units)? {
+        Expression::Float { value: u, .. } => Some (value / u),
+        _ => None,
+    }
+}
+
+/// Converts a dimensionless unit (e.g. `%` or `µm/m`) to LaTeX.
+fn latex_ratio_unit(unit: &str) -> String {
+    unit.replace('%', "\\%")
+        .replace('‰', "\\text{‰}")
+        .replace(['µ', 'μ'], "\\mu ")
+}
+
+/// Gets the rows of a table, rendering each cell with the provided function.
+pub(crate) fn table_rows(columns: &[(String, Expression)], cell: &dyn Fn(&Expression) -> String) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
 
-            output.push_str(&format!(" \\; \\mathrm{{{}}}", name));
+    for (j, (_, column)) in columns.iter().enumerate() {
+        let cells = match column {
+            Expression::Vector (v) => v.iter().map(cell).collect::<Vec<String>>(),
+            e => vec![cell(e)],
+        };
 
-            kg -= unit.1;
-            m -= unit.2;
-            s -= unit.3;
-            a -= unit.4;
-            k -= unit.5;
-            mol -= unit.6;
+        for (i, c) in cells.into_iter().enumerate() {
+            if rows.len() <= i {
+                rows.push(vec![String::new(); columns.len()]);
+            }
+            rows[i][j] = c;
         }
     }
 
-    for (unit, pow) in [
-        ("kg", kg),
-        ("m", m),
-        ("s", s),
-        ("A", a),
-        ("K", k),
-        ("mol", mol),
-    ] {
-        if pow == 1.0 {
-            output.push_str(&format!(" \\; \\mathrm{{{}}}", unit));
-        } else if pow != 0.0 {
-            output.push_str(&format!(" \\; \\mathrm{{{}^{{{}}}}}", unit, pow));
+    rows
+}
+
+/// Formats a table as aligned text.
+fn format_table(columns: &[(String, Expression)], settings: &DisplaySettings) -> String {
+    let rows = table_rows(columns, &|e| e.format(settings));
+
+    // Compute the width of each column
+    let mut widths = columns.iter()
+        .map(|(name, _)| name.len())
+        .collect::<Vec<usize>>();
+    for row in &rows {
+        for (j, cell) in row.iter().enumerate() {
+            widths[j] = widths[j].max(cell.len());
         }
     }
 
-    format!("{:.4}{}", value, output)
+    let mut output = columns.iter()
+        .enumerate()
+        .map(|(j, (name, _))| format!("{:>w$}", name, w = widths[j]))
+        .collect::<Vec<String>>()
+        .join("  ");
+
+    for row in &rows {
+        output.push('\n');
+        output.push_str(&row.iter()
+            .enumerate()
+            .map(|(j, cell)| format!("{:>w$}", cell, w = widths[j]))
+            .collect::<Vec<String>>()
+            .join("  ")
+        );
+    }
+
+    output
 }
 
-impl fmt::Display for Expression {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// Converts a table to a LaTeX array.
+fn latex_table(columns: &[(String, Expression)], settings: &DisplaySettings) -> String {
+    let rows = table_rows(columns, &|e| e.latex(true, settings));
+
+    let mut output = format!("\\begin{{array}}{{{}}}\n", "c".repeat(columns.len()));
+
+    output.push_str(&columns.iter()
+        .map(|(name, _)| latex_identifier(name, settings))
+        .collect::<Vec<String>>()
+        .join(" & ")
+    );
+    output.push_str(" \\\\ \\hline\n");
+
+    for row in &rows {
+        output.push_str(&row.join(" & "));
+        output.push_str(" \\\\\n");
+    }
+
+    output.push_str("\\end{array}");
+
+    output
+}
+
+impl Expression {
+    /// Converts this expression to text.
+    pub fn format(&self, settings: &DisplaySettings) -> String {
         use Expression::*;
 
         let string = match self {
             Assignment {
                 left,
                 right,
-            } => format!("{} = {}", left, right),
+                ..
+            } => format!("{} = {}", left, right.format(settings)),
             Reassignment {
                 left,
                 right,
-            } => format!("{} = {}", left, right),
+                ..
+            } => format!("{} = {}", left, right.format(settings)),
             Float {
                 value,
                 kg,
@@ -264,50 +692,359 @@ impl fmt::Display for Expression {
                 a,
                 k,
                 mol,
-            } => format_unit(*value, *kg, *m, *s, *a, *k, *mol),
+                cd,
+                other,
+            } => format_unit(value.to_owned(), (*kg, *m, *s, *a, *k, *mol, *cd), other, settings),
             Identifier (s) => format!("{}", s),
             Symbolic (s) => format!("{}", s),
             FullSymbolic (s) => format!("{}", s),
+            ShowWork (s) => s.to_string(),
+            Substituted (e) => e.format(settings),
+            Work {
+                left,
+                formula,
+                substituted,
+                value,
+            } => format!("{} = {}", left, work_rows(formula, [
+                formula.format(settings),
+                substituted.format(settings),
+                value.format(settings),
+            ]).join(" = ")),
             BinOp {
                 left,
                 oper,
                 right,
-            } => format!("({} {} {})", left, oper, right),
+            } => format!("({} {} {})", left.format(settings), oper, right.format(settings)),
             FnCall {
                name,
                arguments, 
-            } => carlo_std::format(name, arguments),
+            } => carlo_std::format(name, arguments, settings),
+            NamedArgument {
+                name,
+                value,
+            } => format!("{}: {}", name, value.format(settings)),
+            Text (s) => format!("\"{}\"", s),
+            Vector (v) => format!(
+                "[{}]",
+                v.iter()
+                    .map(|e| e.format(settings))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ),
+            Table (t) => format_table(t, settings),
+            Formatted {
+                value,
+                notation,
+            } => value.format(&settings.with_notation(*notation)),
+            Ratio {
+                value,
+                unit,
+            } => match ratio(value, unit, settings) {
+                Some (n) => format!("{} {}", settings.format_number(&n), unit),
+                None => value.format(settings),
+            },
             Paragraph (s) => format!("\n{}\n", s),
+            Pragma {
+                name,
+                arguments,
+            } => format!("#pragma {} {}", name, arguments.join(" ")).trim().to_string(),
+            UnitDeclaration {
+                name,
+                value,
+            } => match value {
+                Some (v) => format!("1 {} = {}", name, v.format(settings)),
+                None => format!("unit {}", name),
+            },
+            Dimension (s) => format!("dimension {}", s),
             Header (s) => format!("\n{}\n===\n", s.to_uppercase()),
             Subheader (s) => format!("\n{}\n", s.to_uppercase()),
             Subsubheader (s) => format!("\n* {}\n", s),
             Null => "Null".to_string(),
         };
 
-        write!(f, "{}", string)
+        string
     }
 }
 
-/// Converts an identifier to LaTeX.
-fn latex_identifier(id: &str) -> String {
-    let mut output = String::new();
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format(&DisplaySettings::default()))
+    }
+}
 
-    for (i, part) in id.split("_").enumerate() {
-        let lower: &str = &part.to_lowercase();
-        let part = match GREEK.contains(&lower) {
-            true => format!("\\{}", part),
-            false => part.to_string(),
-        };
+/// Chooses the rows of a worked result from its formula, substitution, and value,
+/// omitting the formula if it is a number and the substitution if the formula
+/// has no variables to substitute (or is a single variable).
+pub(crate) fn work_rows(formula: &Expression, rows: [String; 3]) -> Vec<String> {
+    let [formula_row, substituted_row, value_row] = rows;
+    let mut output = Vec::new();
 
-        if i == 0 {
-            output.push_str(&part);
-        } else {
-            output.push_str(&format!(
-                "_{{{}}}",
-                part
-            ))
-        }
+    if !formula.is_numeric() {
+        output.push(formula_row);
+    }
+
+    if formula.has_identifiers() && !matches!(formula, Expression::Identifier (_)) {
+        output.push(substituted_row);
+    }
+
+    if output.last() != Some (&value_row) {
+        output.push(value_row);
     }
 
     output
-}
\ No newline at end of file
+}
+
+/// Converts an expression with values substituted for its variables to LaTeX,
+/// parenthesizing values with units (e.g. `(10 kg) (9.81 m s^-2)`).
+fn latex_substituted(expr: &Expression, toplevel: bool, settings: &DisplaySettings) -> String {
+    use Expression::*;
+
+    match expr {
+        // Fractions separate their operands without parentheses
+        BinOp {
+            left,
+            oper: BinaryOperation::Div,
+            right,
+        } => format!(
+            "\\frac{{{}}}{{{}}}",
+            latex_substituted(left, true, settings),
+            latex_substituted(right, true, settings),
+        ),
+        BinOp {
+            left,
+            oper,
+            right,
+        } => {
+            let output = latex_operation(left, *oper, right, &|e, toplevel| match e {
+                Float { .. } if e.is_dimensionless() => e.latex(true, settings),
+                Float { .. } | Formatted { .. } | Ratio { .. } => format!("\\left({}\\right)", e.latex(true, settings)),
+                e => latex_substituted(e, toplevel, settings),
+            });
+
+            if toplevel {
+                output
+            } else {
+                format!("\\left({}\\right)", output)
+            }
+        },
+        e => e.latex(toplevel, settings),
+    }
+}
+
+/// Converts an addition, subtraction, or multiplication to LaTeX, rendering each
+/// operand with the provided function (which is told whether the operand may be
+/// written without parentheses).
+///
+/// Operands are parenthesized only if they bind more loosely than the operation
+/// (e.g. `(a + b) c` but `a b + c`), and products are written with `\cdot`
+/// where juxtaposition would be ambiguous (e.g. `2 \cdot 3`).
+fn latex_operation(
+    left: &Expression,
+    oper: BinaryOperation,
+    right: &Expression,
+    render: &dyn Fn(&Expression, bool) -> String,
+) -> String {
+    let operand = |e: &Expression, is_right: bool| {
+        let parenthesize = needs_parentheses(e, oper, is_right);
+
+        match e {
+            Expression::BinOp { .. } => render(e, !parenthesize),
+            _ => {
+                let latex = render(e, true);
+
+                // Values may already be parenthesized (e.g. values with units in substitutions)
+                if parenthesize && !latex.starts_with("\\left(") {
+                    format!("\\left({}\\right)", latex)
+                } else {
+                    latex
+                }
+            },
+        }
+    };
+
+    let l = operand(left, false);
+    let r = operand(right, true);
+
+    let symbol = match oper {
+        BinaryOperation::Mul if explicit_product(left, &l, &r, "\\right)") => " \\cdot ".to_string(),
+        _ => oper.latex(),
+    };
+
+    format!("{}{}{}", l, symbol, r)
+}
+
+/// Checks if a product must be written with an explicit operator, given its left
+/// operand and the rendering of each operand, whose parentheses close with `close`.
+///
+/// Numbers cannot be juxtaposed with what precedes them, nor units with what follows them.
+pub(crate) fn explicit_product(left: &Expression, l: &str, r: &str, close: &str) -> bool {
+    r.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.')
+        || (matches!(left, Expression::Float { .. }) && !left.is_dimensionless() && !l.ends_with(close))
+}
+
+/// Checks if an operand of a binary operation must be parenthesized, because it
+/// binds more loosely than the operation or, on the right, as loosely as a
+/// subtraction (e.g. `a - (b - c)`), or because it is a negative number
+/// that would otherwise read as a subtraction.
+pub(crate) fn needs_parentheses(operand: &Expression, oper: BinaryOperation, is_right: bool) -> bool {
+    match operand {
+        // Fractions are never ambiguous
+        Expression::BinOp { oper: BinaryOperation::Div, .. } => false,
+        Expression::BinOp { oper: inner, .. } => inner.precedence() < oper.precedence()
+            || (is_right && inner.precedence() == oper.precedence() && matches!(oper, BinaryOperation::Sub)),
+        Expression::Float { value, .. } => value.to_f64() < 0.0 && (is_right || matches!(oper, BinaryOperation::Mul)),
+        _ => false,
+    }
+}
+
+/// Converts an identifier to LaTeX, using the LaTeX given for it in its
+/// assignment if there is any.
+///
+/// Greek letters are written as symbols, subscripts are separated by commas with
+/// words written upright (e.g. `\sigma_{\mathrm{max}}` for `sigma_max`), and accents
+/// and primes decorate the base (e.g. `\dot{x}'` for `x_dot_prime`).
+fn latex_identifier(id: &str, settings: &DisplaySettings) -> String {
+    if let Some (latex) = settings.names.get(id) {
+        return latex.to_owned();
+    }
+
+    let id = Identifier::decompose(id);
+
+    let mut base = latex_part(&id.base, false);
+    for accent in &id.accents {
+        if let Some ((_, macro_name, ..)) = ACCENTS.iter().find(|(name, ..)| name == accent) {
+            base = format!("{}{{{}}}", macro_name, base);
+        }
+    }
+
+    let primes = "'".repeat(id.primes);
+
+    if id.subscript.is_empty() {
+        format!("{}{}", base, primes)
+    } else {
+        let subscript = id.subscript.iter()
+            .map(|p| latex_part(p, true))
+            .collect::<Vec<String>>();
+
+        format!("{}{}_{{{}}}", base, primes, subscript.join(","))
+    }
+}
+
+/// Converts a part of an identifier to LaTeX, writing words upright in subscripts.
+fn latex_part(part: &Part, subscript: bool) -> String {
+    match part {
+        Part::Greek (latex, ..) => latex.to_string(),
+        Part::Word (w) if subscript => format!("\\mathrm{{{}}}", w),
+        Part::Letter (s) | Part::Word (s) | Part::Number (s) => s.to_string(),
+    }
+}
+
+#[test]
+fn test_prefixes() {
+    let settings = DisplaySettings {
+        notation: Some (Notation::Significant (3)),
+        prefixes: true,
+        ..DisplaySettings::default()
+    };
+
+    let float = |value: f64, kg: f64, m: f64, s: f64| Expression::Float {
+        value: Number::from(value),
+        kg,
+        m,
+        s,
+        a: 0.0,
+        k: 0.0,
+        mol: 0.0,
+        cd: 0.0,
+        other: Dimensions::new(),
+    };
+
+    assert_eq!(float(0.00345, 0.0, 1.0, 0.0).format(&settings), "3.45 mm");
+    assert_eq!(float(2.1E7, 1.0, -1.0, -2.0).format(&settings), "21.0 MPa");
+    assert_eq!(float(1500.0, 0.0, 1.0, -1.0).format(&settings), "1.50 km s^-1");
+    assert_eq!(float(1000.0, 1.0, -3.0, 0.0).format(&settings), "1.00 Mg m^-3");
+    assert_eq!(float(0.005, 1.0, 0.0, 0.0).format(&settings), "5.00 g");
+    assert_eq!(float(2.0, 1.0, 0.0, 0.0).format(&settings), "2.00 kg");
+    assert_eq!(float(0.0, 0.0, 1.0, 0.0).format(&settings), "0.00 m");
+
+    // Units of a unit system are never prefixed unless they are plain units
+    let settings = DisplaySettings {
+        system: crate::UnitSystem::named("mm-N-MPa").unwrap(),
+        ..settings
+    };
+
+    assert_eq!(float(0.00345, 0.0, 1.0, 0.0).format(&settings), "3.45 mm");
+    assert_eq!(float(2.1E7, 1.0, -1.0, -2.0).format(&settings), "21.0 MPa");
+    assert_eq!(float(5000.0, 1.0, 1.0, -2.0).format(&settings), "5.00 kN");
+}
+
+#[test]
+fn test_bytes() {
+    let parser = crate::Parser::new(false);
+    let mut env = crate::Environment::new();
+
+    // Information is displayed in bits unless bytes are preferred
+    let output = env.evaluate(&parser.parse("let a = 3 MiB
+#pragma bytes
+let b = 3 MiB
+let c = 1536 B
+let d = 12 bit
+let r = 2 GiB / 4 s
+#pragma bits
+let e = 2 B
+"));
+    assert_eq!(output.lines().collect::<Vec<&str>>(), [
+        "a = 25165824.0000 bit",
+        "b = 3.0000 MiB",
+        "c = 1.5000 KiB",
+        "d = 1.5000 B",
+        "r = 512.0000 MiB s^-1",
+        "e = 16.0000 bit",
+    ]);
+}
+
+#[test]
+fn test_latex_operations() {
+    let parser = crate::Parser::new(false);
+    let settings = DisplaySettings::default();
+
+    let latex = |input: &str| match &parser.parse(input)[0] {
+        Expression::Assignment { right, .. } => right.latex(true, &settings),
+        e => panic!("expected an assignment, found {:?}", e),
+    };
+
+    assert_eq!(latex("let x = a - (b - c)"), "a - \\left(b - c\\right)");
+    assert_eq!(latex("let x = a - b - c"), "a - b - c");
+    assert_eq!(latex("let x = (a + b) * c"), "\\left(a + b\\right) c");
+    assert_eq!(latex("let x = a * b + c"), "a b + c");
+    assert_eq!(latex("let x = (a * b) * (c * d)"), "a b c d");
+    assert_eq!(latex("let x = a / (b + c)"), "\\frac{a}{b + c}");
+    assert_eq!(latex("let x = a * 2"), "a \\cdot 2");
+    assert_eq!(latex("let x = sqrt(a / b) * c"), "\\sqrt{\\frac{a}{b}} c");
+}
+
+#[test]
+fn test_escape_latex() {
+    assert_eq!(escape_latex("R&D: 50% of $10 #1"), "R\\&D: 50\\% of \\$10 \\#1");
+    assert_eq!(escape_latex("f_{x}"), "f\\_\\{x\\}");
+    assert_eq!(escape_latex("~a^b\\c"), "\\textasciitilde{}a\\textasciicircum{}b\\textbackslash{}c");
+}
+
+#[test]
+fn test_latex_identifiers() {
+    let settings = DisplaySettings::default();
+
+    assert_eq!(latex_identifier("sigma_max", &settings), "\\sigma_{\\mathrm{max}}");
+    assert_eq!(latex_identifier("Delta_x", &settings), "\\Delta_{x}");
+    assert_eq!(latex_identifier("Beta", &settings), "\\mathrm{B}");
+    assert_eq!(latex_identifier("PI", &settings), "PI");
+    assert_eq!(latex_identifier("F_y_Ed", &settings), "F_{y,\\mathrm{Ed}}");
+    assert_eq!(latex_identifier("x_dot_prime_prime", &settings), "\\dot{x}''");
+    assert_eq!(latex_identifier("v_vec_0", &settings), "\\vec{v}_{0}");
+    assert_eq!(latex_identifier("tau_varphi", &settings), "\\tau_{\\varphi}");
+
+    let mut env = crate::Environment::new();
+    let latex = env.latex_evaluate(&crate::Parser::new(false).parse("let sigma_max \"\\sigma_{\\max}\" = 5\nlet y = sigma_max * b_hat\n"));
+    assert!(latex.contains("\\sigma_{\\max} := 5"));
+    assert!(latex.contains("y := \\sigma_{\\max} \\hat{b}"));
+}
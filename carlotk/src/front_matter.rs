@@ -0,0 +1,113 @@
+//! Parses the front matter of Carlo source files.
+
+use crate::Error;
+
+/// Delimiter of a front-matter block
+const DELIMITER: &str = "---";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// Metadata at the top of a source file, written as `key: value` lines
+/// between two `---` lines (e.g. the title and author of a LaTeX document).
+/// Project configuration files use the same `key: value` lines.
+pub struct FrontMatter (Vec<(String, String)>);
+
+impl FrontMatter {
+    /// Parses the front matter of a source file,
+    /// which is empty if the file has no closed front-matter block.
+    pub fn parse(contents: &str) -> Self {
+        let lines = contents.lines().collect::<Vec<&str>>();
+
+        match block(&lines) {
+            Some (end) => Self::fields(&lines[1..end], 2),
+            None => Self::default(),
+        }
+    }
+
+    /// Parses `key: value` lines, the first of which is on the given line,
+    /// ignoring blank lines and comments beginning with `#`.
+    pub fn fields(lines: &[&str], first: usize) -> Self {
+        let mut fields = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            match line.split_once(':') {
+                Some ((key, value)) => fields.push((key.trim().to_lowercase(), unquote(value.trim()).to_string())),
+                None => Error::InvalidFrontMatter ((i + first).to_string(), line.to_string()).warn(),
+            }
+        }
+
+        Self (fields)
+    }
+
+    /// Replaces the front matter of a source file with blank lines
+    /// (so that line numbers are preserved).
+    pub fn strip(contents: &str) -> String {
+        let lines = contents.lines().collect::<Vec<&str>>();
+
+        match block(&lines) {
+            Some (end) => "\n".repeat(end + 1) + &lines[end + 1..].join("\n"),
+            None => contents.to_string(),
+        }
+    }
+
+    /// Gets the value of a field (e.g. `title`), if it was provided.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Checks if there are no fields.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Gets the index of the line that closes the front-matter block
+/// at the top of a file, if there is one.
+fn block(lines: &[&str]) -> Option<usize> {
+    if lines.first()?.trim_end() != DELIMITER {
+        return None;
+    }
+
+    lines.iter()
+        .skip(1)
+        .position(|l| l.trim_end() == DELIMITER)
+        .map(|i| i + 1)
+}
+
+/// Removes matching quotes around a value.
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some (v) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return v;
+        }
+    }
+
+    value
+}
+
+#[test]
+fn test_front_matter() {
+    let input = "---\ntitle: Beam Design\nauthor: \"J. Smith\"\n---\nlet x = 2\n";
+    let front = FrontMatter::parse(input);
+    let body = FrontMatter::strip(input);
+    assert_eq!(front.get("title"), Some ("Beam Design"));
+    assert_eq!(front.get("author"), Some ("J. Smith"));
+    assert_eq!(front.get("date"), None);
+    assert_eq!(body, "\n\n\n\nlet x = 2");
+
+    // Line numbers are preserved
+    let parser = crate::Parser::new(false);
+    let statements = parser.parse_located(&body);
+    assert!(statements.iter().any(|(line, e)| *line == 5 && matches!(e, crate::Expression::Assignment { .. })));
+
+    // Unclosed blocks and files without front matter are left alone
+    for input in ["let x = 2\n", "---\ntitle: x\n"] {
+        assert!(FrontMatter::parse(input).is_empty());
+        assert_eq!(FrontMatter::strip(input), input);
+    }
+}
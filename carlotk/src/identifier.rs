@@ -0,0 +1,322 @@
+//! Decomposes identifiers (e.g. `sigma_max` or `x_dot_prime`) into the parts
+//! from which LaTeX, MathML and Typst write them.
+
+use crate::DisplaySettings;
+
+/// Greek letters written by name in identifiers, each with the LaTeX of its small
+/// letter and capital (written upright where it looks like a Latin letter),
+/// its small letter and capital, and the Typst name of its small letter
+const GREEK: [(&str, &str, &str, char, char, &str); 24] = [
+    ("alpha", "\\alpha", "\\mathrm{A}", 'α', 'Α', "alpha"),
+    ("beta", "\\beta", "\\mathrm{B}", 'β', 'Β', "beta"),
+    ("gamma", "\\gamma", "\\Gamma", 'γ', 'Γ', "gamma"),
+    ("delta", "\\delta", "\\Delta", 'δ', 'Δ', "delta"),
+    ("epsilon", "\\epsilon", "\\mathrm{E}", 'ϵ', 'Ε', "epsilon.alt"),
+    ("zeta", "\\zeta", "\\mathrm{Z}", 'ζ', 'Ζ', "zeta"),
+    ("eta", "\\eta", "\\mathrm{H}", 'η', 'Η', "eta"),
+    ("theta", "\\theta", "\\Theta", 'θ', 'Θ', "theta"),
+    ("iota", "\\iota", "\\mathrm{I}", 'ι', 'Ι', "iota"),
+    ("kappa", "\\kappa", "\\mathrm{K}", 'κ', 'Κ', "kappa"),
+    ("lambda", "\\lambda", "\\Lambda", 'λ', 'Λ', "lambda"),
+    ("mu", "\\mu", "\\mathrm{M}", 'μ', 'Μ', "mu"),
+    ("nu", "\\nu", "\\mathrm{N}", 'ν', 'Ν', "nu"),
+    ("xi", "\\xi", "\\Xi", 'ξ', 'Ξ', "xi"),
+    ("omicron", "o", "\\mathrm{O}", 'ο', 'Ο', "omicron"),
+    ("pi", "\\pi", "\\Pi", 'π', 'Π', "pi"),
+    ("rho", "\\rho", "\\mathrm{P}", 'ρ', 'Ρ', "rho"),
+    ("sigma", "\\sigma", "\\Sigma", 'σ', 'Σ', "sigma"),
+    ("tau", "\\tau", "\\mathrm{T}", 'τ', 'Τ', "tau"),
+    ("upsilon", "\\upsilon", "\\Upsilon", 'υ', 'Υ', "upsilon"),
+    ("phi", "\\phi", "\\Phi", 'ϕ', 'Φ', "phi.alt"),
+    ("chi", "\\chi", "\\mathrm{X}", 'χ', 'Χ', "chi"),
+    ("psi", "\\psi", "\\Psi", 'ψ', 'Ψ', "psi"),
+    ("omega", "\\omega", "\\Omega", 'ω', 'Ω', "omega"),
+];
+
+/// Variant forms of Greek letters, which have no capitals,
+/// each with its LaTeX, its letter, and its Typst name
+const GREEK_VARIANTS: [(&str, &str, char, &str); 6] = [
+    ("varepsilon", "\\varepsilon", 'ε', "epsilon"),
+    ("vartheta", "\\vartheta", 'ϑ', "theta.alt"),
+    ("varpi", "\\varpi", 'ϖ', "pi.alt"),
+    ("varrho", "\\varrho", 'ϱ', "rho.alt"),
+    ("varsigma", "\\varsigma", 'ς', "sigma.alt"),
+    ("varphi", "\\varphi", 'φ', "phi"),
+];
+
+/// Accents written as identifier suffixes (e.g. `x_dot`), each with
+/// its LaTeX macro, its MathML mark, and its Typst function
+pub(crate) const ACCENTS: [(&str, &str, char, &str); 6] = [
+    ("dot", "\\dot", '\u{2D9}', "dot"),
+    ("ddot", "\\ddot", '\u{A8}', "dot.double"),
+    ("hat", "\\hat", '^', "hat"),
+    ("bar", "\\bar", '\u{AF}', "macron"),
+    ("tilde", "\\tilde", '~', "tilde"),
+    ("vec", "\\vec", '\u{2192}', "arrow"),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+/// A part of an identifier.
+pub(crate) enum Part {
+    /// Greek letter, with its LaTeX, its letter, and its Typst name
+    Greek (&'static str, char, String),
+
+    /// Single letter, written in italics
+    Letter (String),
+
+    /// Word of more than one letter (e.g. `max`), written upright in subscripts
+    Word (String),
+
+    /// Number (e.g. `0`)
+    Number (String),
+}
+
+impl Part {
+    /// Classifies a part of an identifier.
+    fn from(text: &str) -> Self {
+        if let Some (greek) = greek(text) {
+            greek
+        } else if text.chars().all(|c| c.is_ascii_digit()) {
+            Part::Number (text.to_string())
+        } else if text.chars().count() == 1 {
+            Part::Letter (text.to_string())
+        } else {
+            Part::Word (text.to_string())
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// An identifier decomposed into a base, decorated by accents (innermost first)
+/// and primes, and a subscript of one or more parts.
+pub(crate) struct Identifier {
+    pub base: Part,
+    pub accents: Vec<&'static str>,
+    pub primes: usize,
+    pub subscript: Vec<Part>,
+}
+
+impl Identifier {
+    /// Decomposes an identifier, or the LaTeX given for it in its assignment
+    /// (e.g. `let sigma_max "\sigma_{\max}" = ...`) if it can be decomposed.
+    pub fn new(id: &str, settings: &DisplaySettings) -> Self {
+        settings.names.get(id)
+            .and_then(|latex| Self::from_latex(latex))
+            .unwrap_or_else(|| Self::decompose(id))
+    }
+
+    /// Decomposes an identifier.
+    ///
+    /// Greek letters are written by name, with capitals matched by case (e.g. `Delta`).
+    /// Anything after the first underscore is a subscript, with parts separated by
+    /// underscores, except for accents and primes, which decorate the base
+    /// (e.g. `x_dot_prime`).
+    pub fn decompose(id: &str) -> Self {
+        let mut parts = id.split('_');
+        let base = Part::from(parts.next().unwrap_or_default());
+        let mut accents = Vec::new();
+        let mut primes = 0;
+        let mut subscript = Vec::new();
+
+        for part in parts.filter(|p| !p.is_empty()) {
+            if part == "prime" {
+                primes += 1;
+            } else if let Some ((name, ..)) = ACCENTS.iter().find(|(name, ..)| *name == part) {
+                accents.push(*name);
+            } else {
+                subscript.push(Part::from(part));
+            }
+        }
+
+        Self {
+            base,
+            accents,
+            primes,
+            subscript,
+        }
+    }
+
+    /// Decomposes the LaTeX written for an identifier (e.g. `\sigma_{\max}` or `\dot{x}'`),
+    /// if it is a base with accents, primes, and a subscript of Greek letters, letters,
+    /// numbers and words (e.g. `\mathrm{Ed}`), so that it can be written in MathML and Typst.
+    pub fn from_latex(latex: &str) -> Option<Self> {
+        let latex = latex.split_whitespace().collect::<String>();
+
+        // The base ends at the first subscript or prime
+        let mut depth = 0;
+        let end = latex.find(|c: char| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => (),
+            }
+            depth == 0 && matches!(c, '_' | '\'' | '^')
+        }).unwrap_or(latex.len());
+
+        let (base, mut accents) = decorated(&latex[..end])?;
+        accents.reverse();
+
+        let mut primes = 0;
+        let mut subscript = Vec::new();
+        let mut rest = &latex[end..];
+
+        while !rest.is_empty() {
+            if let Some (r) = rest.strip_prefix('\'') {
+                primes += 1;
+                rest = r;
+            } else if let Some (r) = rest.strip_prefix("^{\\prime}").or(rest.strip_prefix("^\\prime")) {
+                primes += 1;
+                rest = r;
+            } else if let (Some (r), true) = (rest.strip_prefix('_'), subscript.is_empty()) {
+                let (group, r) = group(r)?;
+                subscript = group.split(',')
+                    .map(part)
+                    .collect::<Option<Vec<Part>>>()?;
+                rest = r;
+            } else {
+                return None;
+            }
+        }
+
+        Some (Self {
+            base,
+            accents,
+            primes,
+            subscript,
+        })
+    }
+}
+
+/// Splits a group (e.g. `{max}`, a command, or a single character) from the start
+/// of LaTeX, returning its contents and the rest of the LaTeX.
+fn group(latex: &str) -> Option<(&str, &str)> {
+    if let Some (command) = latex.strip_prefix('\\') {
+        let end = command.find(|c: char| !c.is_alphabetic()).unwrap_or(command.len());
+        return Some (latex.split_at(end + 1));
+    }
+
+    if !latex.starts_with('{') {
+        let c = latex.chars().next()?;
+        return Some (latex.split_at(c.len_utf8()));
+    }
+
+    let mut depth = 0;
+    for (i, c) in latex.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => (),
+        }
+
+        if depth == 0 {
+            return Some ((&latex[1..i], &latex[i + 1..]));
+        }
+    }
+
+    None
+}
+
+/// Decomposes LaTeX into a part decorated by accents (outermost first).
+fn decorated(latex: &str) -> Option<(Part, Vec<&'static str>)> {
+    for (name, command, ..) in ACCENTS.iter() {
+        if let Some ((inner, "")) = latex.strip_prefix(command).and_then(group) {
+            let (part, mut accents) = decorated(inner)?;
+            accents.insert(0, *name);
+            return Some ((part, accents));
+        }
+    }
+
+    Some ((part(latex)?, Vec::new()))
+}
+
+/// Decomposes LaTeX into a single part (e.g. `\sigma`, `x`, `0` or `\mathrm{max}`).
+fn part(latex: &str) -> Option<Part> {
+    let text = match latex.strip_prefix('\\') {
+        Some (command) => {
+            let (name, rest) = command.split_at(command.find(|c: char| !c.is_alphabetic()).unwrap_or(command.len()));
+
+            match (name, rest) {
+                ("mathrm" | "text" | "operatorname", r) => group(r).filter(|(_, r)| r.is_empty())?.0,
+                (n, "") => return Some (greek_latex(n).unwrap_or(Part::Word (n.to_string()))),
+                _ => return None,
+            }
+        },
+        None => latex,
+    };
+
+    if text.is_empty() || !text.chars().all(char::is_alphanumeric) {
+        None
+    } else if text.chars().all(|c| c.is_ascii_digit()) {
+        Some (Part::Number (text.to_string()))
+    } else if text.chars().count() == 1 && !latex.starts_with('\\') {
+        Some (Part::Letter (text.to_string()))
+    } else {
+        Some (Part::Word (text.to_string()))
+    }
+}
+
+/// Gets a Greek letter written as a LaTeX command (e.g. `Delta` for `\Delta`).
+fn greek_latex(command: &str) -> Option<Part> {
+    let latex = format!("\\{}", command);
+
+    if let Some ((_, l, letter, typst)) = GREEK_VARIANTS.iter().find(|(_, l, ..)| *l == latex) {
+        return Some (Part::Greek (l, *letter, typst.to_string()));
+    }
+
+    GREEK.iter().find_map(|(name, small, capital, s, c, typst)| {
+        if *small == latex {
+            Some (Part::Greek (small, *s, typst.to_string()))
+        } else if *capital == latex {
+            let mut chars = name.chars();
+            let first = chars.next()?.to_uppercase();
+            Some (Part::Greek (capital, *c, format!("{}{}", first, chars.as_str())))
+        } else {
+            None
+        }
+    })
+}
+
+/// Gets a Greek letter written by name (e.g. `Delta`).
+fn greek(name: &str) -> Option<Part> {
+    if let Some ((_, latex, letter, typst)) = GREEK_VARIANTS.iter().find(|(n, ..)| *n == name) {
+        return Some (Part::Greek (latex, *letter, typst.to_string()));
+    }
+
+    GREEK.iter().find_map(|(lower, latex, capital, small, big, typst)| {
+        let mut chars = lower.chars();
+        let first = chars.next()?.to_uppercase();
+
+        if name == *lower {
+            Some (Part::Greek (latex, *small, typst.to_string()))
+        } else if name == format!("{}{}", first, chars.as_str()) {
+            Some (Part::Greek (capital, *big, name.to_string()))
+        } else {
+            None
+        }
+    })
+}
+
+#[test]
+fn test_identifiers() {
+    let id = Identifier::decompose("sigma_max_dot_prime_0");
+
+    assert_eq!(id.base, Part::Greek ("\\sigma", 'σ', "sigma".to_string()));
+    assert_eq!(id.accents, ["dot"]);
+    assert_eq!(id.primes, 1);
+    assert_eq!(id.subscript, [Part::Word ("max".to_string()), Part::Number ("0".to_string())]);
+
+    assert_eq!(Identifier::decompose("Beta").base, Part::Greek ("\\mathrm{B}", 'Β', "Beta".to_string()));
+    assert_eq!(Identifier::decompose("PI").base, Part::Word ("PI".to_string()));
+    assert_eq!(Identifier::decompose("F_y").subscript, [Part::Letter ("y".to_string())]);
+
+    // Identifiers may be decomposed from the LaTeX written for them
+    let id = Identifier::from_latex("\\hat{\\dot{\\Delta}}'_{\\max, 0}").unwrap();
+    assert_eq!(id.base, Part::Greek ("\\Delta", 'Δ', "Delta".to_string()));
+    assert_eq!(id.accents, ["dot", "hat"]);
+    assert_eq!(id.primes, 1);
+    assert_eq!(id.subscript, [Part::Word ("max".to_string()), Part::Number ("0".to_string())]);
+
+    assert_eq!(Identifier::from_latex("F_y").unwrap().subscript, [Part::Letter ("y".to_string())]);
+    assert_eq!(Identifier::from_latex("\\mathrm{EI}").unwrap().base, Part::Word ("EI".to_string()));
+    assert_eq!(Identifier::from_latex("\\frac{a}{b}"), None);
+}
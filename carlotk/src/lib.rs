@@ -2,14 +2,24 @@
 
 mod binary_operation;
 mod carlo_std;
+mod check;
 mod cli;
+mod dimension;
+mod display;
 mod environment;
 mod error;
 mod expression;
+mod front_matter;
 mod help;
+mod identifier;
+mod mathml;
+mod number;
 mod parser;
+mod siunitx;
 mod tokenizer;
+mod typst;
 mod unit;
+mod unit_system;
 
 use std::{
     fs::OpenOptions,
@@ -26,16 +36,43 @@ use colored::*;
 
 pub use binary_operation::BinaryOperation;
 
+pub use check::{
+    Checker,
+    check_program,
+};
+
 pub use cli::{
     CliArgs,
     Flag,
 };
 
+pub use dimension::{
+    Annotation,
+    Dimensions,
+};
+
+pub use display::{
+    DisplaySettings,
+    Notation,
+};
+
 pub use environment::Environment;
 
 pub use error::Error;
 
-pub use expression::Expression;
+pub use expression::{
+    Expression,
+    escape_latex,
+};
+
+pub use front_matter::FrontMatter;
+
+pub use mathml::escape_html;
+
+pub use number::{
+    Number,
+    Rational,
+};
 
 pub use tokenizer::{
     Token,
@@ -45,13 +82,31 @@ pub use tokenizer::{
 
 pub use parser::Parser;
 
+pub use typst::escape_typst;
+
 pub use unit::{
+    BINARY_PREFIXES,
+    CUSTOMARY,
+    DIMENSIONLESS,
+    INFORMATION,
+    INFORMATION_UNITS,
     PREFIXES,
+    UnitDefinition,
+    UnitTable,
+    PREFIXED,
     UNITS,
 };
 
+pub use unit_system::{
+    UnitSystem,
+    UNIT_SYSTEMS,
+};
+
 pub const VERSION: &str = "1.1.0";
 
+/// Name of the project configuration file, read from the directory of a source file
+pub const CONFIG_FILE: &str = "carlo.conf";
+
 /// A prelude for writing subcommands.
 pub mod prelude {
     pub use std::{
@@ -62,16 +117,29 @@ pub mod prelude {
 
     pub use colored::*;
 
-    pub use rustyline::DefaultEditor;
+    pub use rustyline::{
+        DefaultEditor,
+        error::ReadlineError,
+    };
 
     pub use crate::{
         CliArgs,
+        Checker,
+        check_program,
         Environment,
         Error,
+        escape_html,
+        escape_latex,
+        escape_typst,
+        Expression,
         Flag,
+        front_matter,
+        FrontMatter,
         read,
         parse,
+        parse_located,
         Parser,
+        project_config,
         VERSION,
     };
 
@@ -80,17 +148,61 @@ pub mod prelude {
 
 /// Converts a source file into a list of expressions.
 pub fn parse(inputfile: Option<PathBuf>, debug: bool) -> Vec<Expression> {
+    parse_located(inputfile, debug)
+        .into_iter()
+        .map(|(_, e)| e)
+        .collect::<Vec<Expression>>()
+}
+
+/// Converts a source file into a list of expressions,
+/// each with the line on which it begins.
+pub fn parse_located(inputfile: Option<PathBuf>, debug: bool) -> Vec<(usize, Expression)> {
     if debug {
         println!("{} running Carlo in debug mode", "(notice)".truecolor(220, 180, 0).bold());
         println!();
     }
 
     // Read data from input file
-    let f = match inputfile {
-        Some (i) => i,
+    let contents = match inputfile {
+        Some (f) => read_source(f),
         None => return Vec::new(),
     };
 
+    // Front matter is not code
+    let contents = FrontMatter::strip(&contents);
+
+    // Construct parser
+    let parser = Parser::new(debug);
+
+    parser.parse_located(&contents)
+}
+
+/// Reads the front matter of a source file.
+pub fn front_matter(inputfile: Option<PathBuf>) -> FrontMatter {
+    match inputfile {
+        Some (f) => FrontMatter::parse(&read_source(f)),
+        None => FrontMatter::default(),
+    }
+}
+
+/// Reads the project configuration (`carlo.conf`) in the directory of a source file,
+/// which is empty if there is no configuration file.
+pub fn project_config(inputfile: Option<PathBuf>) -> FrontMatter {
+    let config = match inputfile {
+        Some (f) => f.with_file_name(CONFIG_FILE),
+        None => return FrontMatter::default(),
+    };
+
+    if !config.is_file() {
+        return FrontMatter::default();
+    }
+
+    let contents = read_source(config);
+    FrontMatter::fields(&contents.lines().collect::<Vec<&str>>(), 1)
+}
+
+/// Reads the contents of a source file.
+fn read_source(f: PathBuf) -> String {
     let strf = format!("{}", f.display());
 
     let option_file = OpenOptions::new()
@@ -109,10 +221,7 @@ pub fn parse(inputfile: Option<PathBuf>, debug: bool) -> Vec<Expression> {
         _ => Error::CouldNotReadFile (&strf).throw(),
     };
 
-    // Construct parser
-    let parser = Parser::new(debug);
-
-    parser.parse(&contents)
+    contents
 }
 
 /// Displays a prompt and reads user input.
@@ -164,32 +273,52 @@ macro_rules! construct_std {
     (
         $(func $f: ident)*
     ) => {
-        // Import Expression enum
-        use crate::Expression;
+        // Import Expression enum and display settings
+        use crate::{
+            DisplaySettings,
+            Expression,
+            UnitTable,
+        };
 
         // Add each function as a module
         $( mod $f; )*
 
         /// Calls the requested standard library function.
-        pub fn call(name: &str, arguments: &Vec<Expression>) -> Expression {
+        pub fn call(name: &str, arguments: &[Expression], units: &UnitTable) -> Expression {
             match name {
-                $( stringify!($f) => $f::eval(arguments), )*
+                $( stringify!($f) => $f::eval(arguments, units), )*
                 _ => Expression::Null,
             }
         }
 
         /// Converts the requested standard library function to text.
-        pub fn format(name: &str, arguments: &Vec<Expression>) -> String {
+        pub fn format(name: &str, arguments: &[Expression], settings: &DisplaySettings) -> String {
             match name {
-                $( stringify!($f) => $f::format(arguments), )*
+                $( stringify!($f) => $f::format(arguments, settings), )*
                 _ => String::new(),
             }
         }
 
         /// Converts the requested standard library function to LaTeX.
-        pub fn latex(name: &str, arguments: &Vec<Expression>) -> String {
+        pub fn latex(name: &str, arguments: &[Expression], settings: &DisplaySettings) -> String {
+            match name {
+                $( stringify!($f) => $f::latex(arguments, settings), )*
+                _ => String::new(),
+            }
+        }
+
+        /// Converts the requested standard library function to MathML.
+        pub fn mathml(name: &str, arguments: &[Expression], settings: &DisplaySettings) -> String {
+            match name {
+                $( stringify!($f) => $f::mathml(arguments, settings), )*
+                _ => String::new(),
+            }
+        }
+
+        /// Converts the requested standard library function to Typst.
+        pub fn typst(name: &str, arguments: &[Expression], settings: &DisplaySettings) -> String {
             match name {
-                $( stringify!($f) => $f::latex(arguments), )*
+                $( stringify!($f) => $f::typst(arguments, settings), )*
                 _ => String::new(),
             }
         }
@@ -0,0 +1,361 @@
+//! Renders expressions as MathML, which browsers display without any scripts.
+
+use crate::{
+    BinaryOperation,
+    carlo_std,
+    Dimensions,
+    DisplaySettings,
+    Expression,
+    Number,
+    expression::{
+        needs_parentheses,
+        ratio,
+        table_rows,
+        unit_parts,
+        work_rows,
+    },
+    identifier::{
+        ACCENTS,
+        Identifier,
+        Part,
+    },
+};
+
+/// Space between a value and its units
+const UNIT_SPACE: &str = "<mspace width=\"0.278em\"/>";
+
+/// Escapes the characters of text that are special in HTML and MathML.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Wraps MathML in a `math` element, displayed as a block or inline with text.
+pub(crate) fn math(mathml: &str, block: bool) -> String {
+    if block {
+        format!("<math display=\"block\"><mrow>{}</mrow></math>", mathml)
+    } else {
+        format!("<math><mrow>{}</mrow></math>", mathml)
+    }
+}
+
+impl Expression {
+    /// Converts this expression to MathML.
+    pub fn mathml(&self, toplevel: bool, settings: &DisplaySettings) -> String {
+        use Expression::*;
+
+        match self {
+            Assignment {
+                left,
+                right,
+                ..
+            } => format!("{}<mo>:=</mo>{}", mathml_identifier(left, settings), right.mathml(true, settings)),
+            Reassignment {
+                left,
+                right,
+                ..
+            } => format!("{}<mo>=</mo>{}", mathml_identifier(left, settings), right.mathml(true, settings)),
+            Float {
+                value,
+                kg,
+                m,
+                s,
+                a,
+                k,
+                mol,
+                cd,
+                other,
+            } => mathml_unit(value.to_owned(), (*kg, *m, *s, *a, *k, *mol, *cd), other, settings),
+            Identifier (s) => mathml_identifier(s, settings),
+            Symbolic (s) | FullSymbolic (s) | ShowWork (s) => mathml_identifier(s, settings),
+            Substituted (e) => mathml_substituted(e, toplevel, settings),
+            Work {
+                left,
+                formula,
+                substituted,
+                value,
+            } => {
+                let rows = work_rows(formula, [
+                    formula.mathml(true, settings),
+                    mathml_substituted(substituted, true, settings),
+                    value.mathml(true, settings),
+                ]);
+
+                let mut output = String::from("<mtable columnalign=\"right left\">");
+                for (i, row) in rows.iter().enumerate() {
+                    let left = if i == 0 { mathml_identifier(left, settings) } else { String::new() };
+                    output.push_str(&format!("<mtr><mtd>{}</mtd><mtd><mo>=</mo>{}</mtd></mtr>", left, row));
+                }
+                output.push_str("</mtable>");
+
+                output
+            },
+            BinOp {
+                left,
+                oper: BinaryOperation::Div,
+                right,
+            } => format!("<mfrac><mrow>{}</mrow><mrow>{}</mrow></mfrac>", left.mathml(true, settings), right.mathml(true, settings)),
+            BinOp {
+                left,
+                oper,
+                right,
+            } => {
+                let output = mathml_operation(left, *oper, right, &|e, toplevel| e.mathml(toplevel, settings));
+
+                if toplevel {
+                    format!("<mrow>{}</mrow>", output)
+                } else {
+                    format!("<mrow><mo>(</mo>{}<mo>)</mo></mrow>", output)
+                }
+            },
+            FnCall {
+                name,
+                arguments,
+            } => carlo_std::mathml(name, arguments, settings),
+            NamedArgument {
+                name,
+                value,
+            } => format!("<mi mathvariant=\"normal\">{}</mi><mo>=</mo>{}", escape_html(name), value.mathml(true, settings)),
+            Text (s) => format!("<mtext>&#x201C;{}&#x201D;</mtext>", escape_html(s)),
+            Vector (v) => format!(
+                "<mrow><mo>[</mo>{}<mo>]</mo></mrow>",
+                v.iter()
+                    .map(|e| e.mathml(true, settings))
+                    .collect::<Vec<String>>()
+                    .join("<mo>,</mo>"),
+            ),
+            Table (t) => mathml_table(t, settings),
+            Formatted {
+                value,
+                notation,
+            } => value.mathml(toplevel, &settings.with_notation(*notation)),
+            Ratio {
+                value,
+                unit,
+            } => match ratio(value, unit, settings) {
+                Some (n) => format!(
+                    "{}{}<mi mathvariant=\"normal\">{}</mi>",
+                    settings.mathml_number(&n),
+                    UNIT_SPACE,
+                    escape_html(unit),
+                ),
+                None => value.mathml(toplevel, settings),
+            },
+            Paragraph (s) | Dimension (s) | Header (s) | Subheader (s) | Subsubheader (s) => {
+                format!("<mtext>{}</mtext>", escape_html(s))
+            },
+            UnitDeclaration {
+                name,
+                value,
+            } => match value {
+                Some (v) => format!(
+                    "<mn>1</mn>{}<mi mathvariant=\"normal\">{}</mi><mo>=</mo>{}",
+                    UNIT_SPACE,
+                    escape_html(name),
+                    v.mathml(true, settings),
+                ),
+                None => format!("<mi mathvariant=\"normal\">{}</mi>", escape_html(name)),
+            },
+            Pragma { .. } | Null => String::new(),
+        }
+    }
+}
+
+/// Converts a value and its units to MathML.
+fn mathml_unit(
+    value: Number,
+    dimensions: (f64, f64, f64, f64, f64, f64, f64),
+    other: &Dimensions,
+    settings: &DisplaySettings,
+) -> String {
+    let (value, parts) = unit_parts(value, dimensions, other, settings);
+    let mut output = settings.mathml_number(&value);
+
+    for (prefix, unit, pow) in parts {
+        let prefix = match prefix {
+            Some ("u") => "µ",
+            Some (p) => p,
+            None => "",
+        };
+        let unit = format!("<mi mathvariant=\"normal\">{}{}</mi>", prefix, escape_html(&unit));
+
+        output.push_str(UNIT_SPACE);
+        if pow == 1.0 {
+            output.push_str(&unit);
+        } else {
+            output.push_str(&format!("<msup>{}<mn>{}</mn></msup>", unit, pow));
+        }
+    }
+
+    output
+}
+
+/// Converts a table to MathML, with its column names as the first row.
+fn mathml_table(columns: &[(String, Expression)], settings: &DisplaySettings) -> String {
+    let rows = table_rows(columns, &|e| e.mathml(true, settings));
+
+    let mut output = String::from("<mtable><mtr>");
+    for (name, _) in columns {
+        output.push_str(&format!("<mtd>{}</mtd>", mathml_identifier(name, settings)));
+    }
+    output.push_str("</mtr>");
+
+    for row in &rows {
+        output.push_str("<mtr>");
+        for cell in row {
+            output.push_str(&format!("<mtd>{}</mtd>", cell));
+        }
+        output.push_str("</mtr>");
+    }
+
+    output.push_str("</mtable>");
+
+    output
+}
+
+/// Converts an expression with values substituted for its variables to MathML,
+/// parenthesizing values with units (e.g. `(10 kg) (9.81 m s^-2)`).
+fn mathml_substituted(expr: &Expression, toplevel: bool, settings: &DisplaySettings) -> String {
+    use Expression::*;
+
+    match expr {
+        BinOp {
+            left,
+            oper,
+            right,
+        } => {
+            let operand = |e: &Expression, toplevel: bool| match e {
+                Float { .. } if e.is_dimensionless() => e.mathml(true, settings),
+                Float { .. } | Formatted { .. } | Ratio { .. } => format!("<mrow><mo>(</mo>{}<mo>)</mo></mrow>", e.mathml(true, settings)),
+                e => mathml_substituted(e, toplevel, settings),
+            };
+
+            if matches!(oper, BinaryOperation::Div) {
+                return format!("<mfrac><mrow>{}</mrow><mrow>{}</mrow></mfrac>", operand(left, true), operand(right, true));
+            }
+
+            let output = mathml_operation(left, *oper, right, &operand);
+
+            if toplevel {
+                format!("<mrow>{}</mrow>", output)
+            } else {
+                format!("<mrow><mo>(</mo>{}<mo>)</mo></mrow>", output)
+            }
+        },
+        e => e.mathml(toplevel, settings),
+    }
+}
+
+/// Converts an addition, subtraction, or multiplication to MathML, rendering each
+/// operand with the provided function (which is told whether the operand may be
+/// written without parentheses).
+///
+/// Operands are parenthesized only if they bind more loosely than the operation
+/// (e.g. `(a + b) c` but `a b + c`).
+fn mathml_operation(
+    left: &Expression,
+    oper: BinaryOperation,
+    right: &Expression,
+    render: &dyn Fn(&Expression, bool) -> String,
+) -> String {
+    let operand = |e: &Expression, is_right: bool| {
+        let parenthesize = needs_parentheses(e, oper, is_right);
+
+        match e {
+            Expression::BinOp { .. } => render(e, !parenthesize),
+            _ => {
+                let mathml = render(e, true);
+
+                // Values may already be parenthesized (e.g. values with units in substitutions)
+                if parenthesize && !mathml.starts_with("<mrow><mo>(</mo>") {
+                    format!("<mrow><mo>(</mo>{}<mo>)</mo></mrow>", mathml)
+                } else {
+                    mathml
+                }
+            },
+        }
+    };
+
+    format!("{}{}{}", operand(left, false), oper.mathml(), operand(right, true))
+}
+
+/// Converts an identifier to MathML, writing Greek letters as symbols, subscripts
+/// separated by commas with numbers as numbers (e.g. `σ` with `max`, or `x` with `0`),
+/// and accents and primes over and after the base (e.g. `ẋ′` for `x_dot_prime`),
+/// or as the LaTeX given for it in its assignment.
+pub(crate) fn mathml_identifier(id: &str, settings: &DisplaySettings) -> String {
+    let id = Identifier::new(id, settings);
+
+    let mut base = mathml_part(&id.base);
+    for accent in &id.accents {
+        if let Some ((.., mark, _)) = ACCENTS.iter().find(|(name, ..)| name == accent) {
+            base = format!("<mover accent=\"true\">{}<mo>{}</mo></mover>", base, mark);
+        }
+    }
+
+    let primes = format!("<mo>{}</mo>", "\u{2032}".repeat(id.primes));
+    let subscript = id.subscript.iter()
+        .map(mathml_part)
+        .collect::<Vec<String>>()
+        .join("<mo>,</mo>");
+
+    match (subscript.is_empty(), id.primes) {
+        (true, 0) => base,
+        (true, _) => format!("<msup>{}{}</msup>", base, primes),
+        (false, 0) => format!("<msub>{}<mrow>{}</mrow></msub>", base, subscript),
+        (false, _) => format!("<msubsup>{}<mrow>{}</mrow>{}</msubsup>", base, subscript, primes),
+    }
+}
+
+/// Converts a part of an identifier to MathML.
+fn mathml_part(part: &Part) -> String {
+    match part {
+        Part::Greek (_, c, _) => format!("<mi>{}</mi>", c),
+        Part::Number (n) => format!("<mn>{}</mn>", n),
+        Part::Letter (s) | Part::Word (s) => format!("<mi>{}</mi>", escape_html(s)),
+    }
+}
+
+#[test]
+fn test_mathml() {
+    let settings = DisplaySettings::default();
+
+    assert_eq!(mathml_identifier("sigma_max", &settings), "<msub><mi>σ</mi><mrow><mi>max</mi></mrow></msub>");
+    assert_eq!(mathml_identifier("Delta", &settings), "<mi>Δ</mi>");
+    assert_eq!(mathml_identifier("x_0", &settings), "<msub><mi>x</mi><mrow><mn>0</mn></mrow></msub>");
+
+    let parser = crate::Parser::new(false);
+    let mathml = |input: &str| match &parser.parse(input)[0] {
+        Expression::Assignment { right, .. } => right.mathml(true, &settings),
+        e => panic!("expected an assignment, found {:?}", e),
+    };
+
+    assert_eq!(mathml("let x = a / (b + c)"), "<mfrac><mrow><mi>a</mi></mrow><mrow><mrow><mi>b</mi><mo>+</mo><mi>c</mi></mrow></mrow></mfrac>");
+
+    // Operands are parenthesized only where they bind more loosely than the operation
+    assert_eq!(mathml("let x = w * L * L"), "<mrow><mrow><mi>w</mi><mo>&#x2062;</mo><mi>L</mi></mrow><mo>&#x2062;</mo><mi>L</mi></mrow>");
+    assert_eq!(mathml("let x = (a + b) * c"), "<mrow><mrow><mo>(</mo><mi>a</mi><mo>+</mo><mi>b</mi><mo>)</mo></mrow><mo>&#x2062;</mo><mi>c</mi></mrow>");
+    assert_eq!(mathml("let x = a - (b - c)"), "<mrow><mi>a</mi><mo>-</mo><mrow><mo>(</mo><mi>b</mi><mo>-</mo><mi>c</mi><mo>)</mo></mrow></mrow>");
+    assert_eq!(mathml("let x = a * b + c"), "<mrow><mrow><mi>a</mi><mo>&#x2062;</mo><mi>b</mi></mrow><mo>+</mo><mi>c</mi></mrow>");
+
+    let value = Expression::Float {
+        value: Number::from(9.81),
+        kg: 0.0,
+        m: 1.0,
+        s: -2.0,
+        a: 0.0,
+        k: 0.0,
+        mol: 0.0,
+        cd: 0.0,
+        other: Dimensions::new(),
+    };
+    assert_eq!(
+        value.mathml(true, &settings),
+        "<mn>9.8100</mn><mspace width=\"0.278em\"/><mi mathvariant=\"normal\">m</mi>\
+        <mspace width=\"0.278em\"/><msup><mi mathvariant=\"normal\">s</mi><mn>-2</mn></msup>",
+    );
+
+    assert_eq!(escape_html("a < b & c"), "a &lt; b &amp; c");
+}
@@ -0,0 +1,466 @@
+//! Defines exact and approximate numbers.
+
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{
+        Add,
+        Div,
+        Mul,
+        Neg,
+        Sub,
+    },
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{
+    One,
+    Pow,
+    Signed,
+    ToPrimitive,
+    Zero,
+};
+
+use crate::Error;
+
+/// Largest power of ten in a decimal literal that is represented exactly,
+/// beyond which its digits would take too long to compute
+const MAX_EXPONENT: u32 = 4096;
+
+/// Largest integer power to which an exact number is raised exactly
+const MAX_POWER: u32 = 4096;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// An exact rational number of arbitrary precision.
+pub struct Rational (BigRational);
+
+impl Rational {
+    /// Constructs a new rational number in lowest terms.
+    pub fn new(num: BigInt, den: BigInt) -> Option<Self> {
+        if den.is_zero() {
+            return None;
+        }
+
+        Some (Self (BigRational::new(num, den)))
+    }
+
+    /// Constructs a rational number from an integer.
+    pub fn integer(num: i64) -> Self {
+        Self (BigRational::from_integer(BigInt::from(num)))
+    }
+
+    /// Parses a decimal literal such as `-12.5` or `6.02e23`.
+    pub fn parse(input: &str) -> Option<Self> {
+        let (mantissa, exponent) = match input.find(['e', 'E']) {
+            Some (i) => (&input[..i], str::parse::<i64>(&input[i + 1..]).ok()?),
+            None => (input, 0),
+        };
+
+        let (negative, mantissa) = match mantissa.strip_prefix('-') {
+            Some (m) => (true, m),
+            None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+        };
+
+        let (whole, fraction) = match mantissa.split_once('.') {
+            Some ((w, f)) => (w, f),
+            None => (mantissa, ""),
+        };
+
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+
+        let mut num = BigInt::zero();
+        for c in whole.chars().chain(fraction.chars()) {
+            num = num * 10 + c.to_digit(10)?;
+        }
+        if negative {
+            num = -num;
+        }
+
+        let exponent = exponent.checked_sub(fraction.len() as i64)?;
+        if exponent.unsigned_abs() > MAX_EXPONENT as u64 {
+            return None;
+        }
+        let scale = BigInt::from(10).pow(exponent.unsigned_abs() as u32);
+
+        if exponent >= 0 {
+            Self::new(num * scale, BigInt::one())
+        } else {
+            Self::new(num, scale)
+        }
+    }
+
+    /// Converts this rational number to a floating-point number.
+    pub fn to_f64(&self) -> f64 {
+        self.0.to_f64().unwrap_or(f64::NAN)
+    }
+
+    /// Gets the numerator of this rational number.
+    pub fn numerator(&self) -> &BigInt {
+        self.0.numer()
+    }
+
+    /// Gets the denominator of this rational number (always positive).
+    pub fn denominator(&self) -> &BigInt {
+        self.0.denom()
+    }
+
+    /// Checks if this rational number is negative.
+    pub fn is_negative(&self) -> bool {
+        self.0.is_negative()
+    }
+
+    /// Checks if this rational number has a finite decimal expansion.
+    pub fn is_terminating(&self) -> bool {
+        self.decimal_places().is_some()
+    }
+
+    /// Gets the number of decimal places in the expansion of this
+    /// rational number, if it terminates.
+    fn decimal_places(&self) -> Option<u32> {
+        let mut den = self.denominator().to_owned();
+        let mut twos = 0;
+        let mut fives = 0;
+
+        while (&den % 2u32).is_zero() {
+            den /= 2u32;
+            twos += 1;
+        }
+        while (&den % 5u32).is_zero() {
+            den /= 5u32;
+            fives += 1;
+        }
+
+        if den.is_one() {
+            Some (twos.max(fives))
+        } else {
+            None
+        }
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some (Self (&self.0 + &other.0))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some (Self (&self.0 - &other.0))
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some (Self (&self.0 * &other.0))
+    }
+
+    /// Divides this rational number by another, unless the other is zero.
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        if other.0.is_zero() {
+            None
+        } else {
+            Some (Self (&self.0 / &other.0))
+        }
+    }
+
+    /// Raises this rational number to an integer power, unless the power
+    /// is too large or zero is raised to a negative power.
+    pub fn checked_pow(&self, exp: i32) -> Option<Self> {
+        if exp.unsigned_abs() > MAX_POWER || (exp < 0 && self.0.is_zero()) {
+            return None;
+        }
+
+        Some (Self (Pow::pow(&self.0, exp)))
+    }
+
+    /// Takes the square root of this rational number, if it is exact.
+    pub fn sqrt(&self) -> Option<Self> {
+        if self.is_negative() {
+            return None;
+        }
+
+        let (n, d) = (self.numerator().sqrt(), self.denominator().sqrt());
+
+        if &(&n * &n) == self.numerator() && &(&d * &d) == self.denominator() {
+            Self::new(n, d)
+        } else {
+            None
+        }
+    }
+}
+
+/// Displays a rational number as a decimal if it terminates
+/// and as a fraction otherwise.
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let places = match self.decimal_places() {
+            Some (p) => p,
+            None => return write!(f, "{}/{}", self.numerator(), self.denominator()),
+        };
+
+        let scaled = self.numerator() * (BigInt::from(10).pow(places) / self.denominator());
+        let digits = scaled.magnitude().to_string();
+
+        let sign = if self.is_negative() { "-" } else { "" };
+        let places = places as usize;
+
+        if places == 0 {
+            write!(f, "{}{}", sign, digits)
+        } else {
+            let digits = format!("{:0>width$}", digits, width = places + 1);
+            let (whole, fraction) = digits.split_at(digits.len() - places);
+            write!(f, "{}{}.{}", sign, whole, fraction)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A number that is either exact (rational) or approximate (floating-point).
+pub enum Number {
+    /// 64-bit floating-point approximation
+    Approx (f64),
+
+    /// Exact rational number
+    Exact (Rational),
+}
+
+impl Number {
+    /// Constructs an exact number from the shortest decimal
+    /// representation of a floating-point number (e.g. `0.001`),
+    /// falling back to an approximation if it cannot be represented.
+    pub fn exact(value: f64) -> Self {
+        if !value.is_finite() {
+            return Self::Approx (value);
+        }
+
+        match Rational::parse(&format!("{:e}", value)) {
+            Some (r) => Self::Exact (r),
+            None => Self::Approx (value),
+        }
+    }
+
+    /// Parses a numeric literal into an exact number, falling back to an
+    /// approximation if it is too large or too precise to be represented.
+    pub fn parse(input: &str) -> Option<Self> {
+        match Rational::parse(input) {
+            Some (r) => Some (Self::Exact (r)),
+            None => Some (Self::Approx (str::parse::<f64>(input).ok()?)),
+        }
+    }
+
+    /// Checks if this number is zero.
+    pub fn is_zero(&self) -> bool {
+        self.to_f64() == 0.0
+    }
+
+    /// Converts this number to a floating-point number.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Self::Approx (f) => *f,
+            Self::Exact (r) => r.to_f64(),
+        }
+    }
+
+    /// Checks if this number is exact.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Self::Exact (_))
+    }
+
+    /// Converts this number to an approximation.
+    pub fn approx(&self) -> Self {
+        Self::Approx (self.to_f64())
+    }
+
+    /// Converts this number to an exact number, rounding
+    /// approximations to the given number of significant digits.
+    pub fn round_exact(&self, digits: usize) -> Self {
+        match self {
+            Self::Approx (f) if f.is_finite() => {
+                match Rational::parse(&format!("{:.*e}", digits.saturating_sub(1), f)) {
+                    Some (r) => Self::Exact (r),
+                    None => self.to_owned(),
+                }
+            },
+            _ => self.to_owned(),
+        }
+    }
+
+    /// Raises this number to a power, exactly if possible.
+    pub fn pow(&self, exp: f64) -> Self {
+        match self {
+            Self::Exact (r) if exp.fract() == 0.0 => match r.checked_pow(exp as i32) {
+                Some (p) => Self::Exact (p),
+                None => {
+                    Error::InexactNumber (format!("{}^{}", r, exp)).warn();
+                    Self::Approx (r.to_f64().powf(exp))
+                },
+            },
+            _ => Self::Approx (self.to_f64().powf(exp)),
+        }
+    }
+
+    /// Takes the square root of this number, exactly if possible.
+    pub fn sqrt(&self) -> Self {
+        match self {
+            Self::Exact (r) => match r.sqrt() {
+                Some (s) => Self::Exact (s),
+                None => {
+                    Error::InexactNumber (format!("sqrt({})", r)).warn();
+                    Self::Approx (r.to_f64().sqrt())
+                },
+            },
+            Self::Approx (f) => Self::Approx (f.sqrt()),
+        }
+    }
+
+    /// Converts this number to LaTeX, showing non-terminating
+    /// exact numbers as fractions.
+    pub fn latex(&self, decimals: usize) -> String {
+        match self {
+            Self::Exact (r) if !r.is_terminating() => {
+                let sign = if r.is_negative() { "-" } else { "" };
+                format!("{}\\frac{{{}}}{{{}}}", sign, r.numerator().magnitude(), r.denominator())
+            },
+            _ => format!("{:.*}", decimals, self),
+        }
+    }
+
+    /// Converts this number to MathML, showing exact numbers that are not
+    /// terminating decimals as fractions.
+    pub fn mathml(&self, decimals: usize) -> String {
+        match self {
+            Self::Exact (r) if !r.is_terminating() => {
+                let sign = if r.is_negative() { "<mo>-</mo>" } else { "" };
+                format!("{}<mfrac><mn>{}</mn><mn>{}</mn></mfrac>", sign, r.numerator().magnitude(), r.denominator())
+            },
+            _ => format!("<mn>{:.*}</mn>", decimals, self),
+        }
+    }
+
+    /// Converts this number to Typst, showing exact numbers that are not
+    /// terminating decimals as fractions.
+    pub fn typst(&self, decimals: usize) -> String {
+        match self {
+            Self::Exact (r) if !r.is_terminating() => {
+                let sign = if r.is_negative() { "-" } else { "" };
+                format!("{}frac({}, {})", sign, r.numerator().magnitude(), r.denominator())
+            },
+            _ => format!("{:.*}", decimals, self),
+        }
+    }
+
+    /// Applies an exact operation, falling back to floating-point arithmetic
+    /// (and warning the user if both numbers were exact).
+    fn operate(
+        self,
+        other: Self,
+        symbol: &str,
+        exact: fn(&Rational, &Rational) -> Option<Rational>,
+        approx: fn(f64, f64) -> f64,
+    ) -> Self {
+        if let (Self::Exact (l), Self::Exact (r)) = (&self, &other) {
+            match exact(l, r) {
+                Some (result) => return Self::Exact (result),
+                None => Error::InexactNumber (format!("{} {} {}", l, symbol, r)).warn(),
+            }
+        }
+
+        Self::Approx (approx(self.to_f64(), other.to_f64()))
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Self::Approx (value)
+    }
+}
+
+impl Add for Number {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.operate(other, "+", Rational::checked_add, |l, r| l + r)
+    }
+}
+
+impl Sub for Number {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.operate(other, "-", Rational::checked_sub, |l, r| l - r)
+    }
+}
+
+impl Mul for Number {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.operate(other, "*", Rational::checked_mul, |l, r| l * r)
+    }
+}
+
+impl Div for Number {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        if other.is_zero() {
+            Error::DivisionByZero (format!("{} / {}", self, other)).warn();
+            return Self::Approx (self.to_f64() / other.to_f64());
+        }
+
+        self.operate(other, "/", Rational::checked_div, |l, r| l / r)
+    }
+}
+
+impl Neg for Number {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::Exact (Rational::integer(0)) - self
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.to_f64().partial_cmp(&other.to_f64())
+    }
+}
+
+/// Displays a number.  Approximations respect the requested precision,
+/// while exact numbers are always displayed exactly.
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, f.precision()) {
+            (Self::Approx (x), Some (p)) => write!(f, "{:.*}", p, x),
+            (Self::Approx (x), None) => write!(f, "{}", x),
+            (Self::Exact (r), _) => write!(f, "{}", r),
+        }
+    }
+}
+
+#[test]
+fn test_exact_arithmetic() {
+    let a = Number::parse("0.1").unwrap();
+    let b = Number::parse("0.2").unwrap();
+
+    assert_eq!((a.to_owned() + b).to_string(), "0.3");
+    assert_eq!((a / Number::parse("3").unwrap()).to_string(), "1/30");
+    assert_eq!(Number::parse("6.02e23").unwrap().to_string(), "602000000000000000000000");
+    assert_eq!(Number::parse("2.25").unwrap().sqrt().to_string(), "1.5");
+    assert_eq!(Number::exact(0.001).pow(-2.0).to_string(), "1000000");
+    assert!(!Number::parse("2").unwrap().sqrt().is_exact());
+}
+
+#[test]
+fn test_exact_overflow() {
+    let n = |s: &str| Number::parse(s).unwrap();
+
+    // Exact numbers are not limited to 128 bits
+    let max = n("170141183460469231731687303715884105727");
+    assert_eq!((max + n("1")).to_string(), "170141183460469231731687303715884105728");
+    assert_eq!((n("1e300") * n("1e300")).to_string(), format!("1{}", "0".repeat(600)));
+    assert_eq!((n("1e-300") / n("3e300")).to_string(), format!("1/3{}", "0".repeat(600)));
+    assert!(n("2").pow(200.0).is_exact());
+
+    // Numbers that cannot be exact become approximations
+    assert!(!n("1e5000").is_exact());
+    assert!(!(n("1") / n("0")).is_exact());
+}
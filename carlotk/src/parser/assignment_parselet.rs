@@ -1,6 +1,8 @@
 //! Defines an assignment parselet.
 
 use super::{
+    Annotation,
+    annotation_value,
     Expression,
     Parser,
     PrefixParselet,
@@ -18,15 +20,44 @@ impl PrefixParselet for AssignmentParselet {
         // Parse left
         let left = &tokenstream.get(TokenClass::Identifier).value;
 
+        // Parse annotation
+        let annotation = match tokenstream.peek() {
+            Some (t) if t.check(TokenClass::Annotation) => {
+                tokenstream.next();
+
+                // Invalid annotations are kept so that they are reported as errors
+                Some (Annotation {
+                    text: t.value.to_owned(),
+                    dimensions: annotation_value(&t.value, &parser.units.borrow()).map(Box::new),
+                })
+            },
+            _ => None,
+        };
+
+        // Parse LaTeX override
+        let latex = match tokenstream.peek() {
+            Some (t) if t.check(TokenClass::Text) => {
+                tokenstream.next();
+                Some (t.value.to_owned())
+            },
+            _ => None,
+        };
+
         // Discard equals sign
         tokenstream.get(TokenClass::Assignment);
 
         // Parse right
         let right = parser.parse_expr(tokenstream, token.precedence(), nesting + 1);
 
+        // A variable is only a variable after its definition
+        parser.variables.borrow_mut().insert(left.to_owned());
+
         Assignment {
             left: left.to_owned(),
             right: Box::new(right),
+            annotation,
+            latex,
+            line: token.line,
         }
     }
-}
\ No newline at end of file
+}
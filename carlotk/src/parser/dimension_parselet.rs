@@ -0,0 +1,32 @@
+//! Defines a dimension declaration parselet.
+
+use super::{
+    Error,
+    Expression,
+    Parser,
+    PrefixParselet,
+    Token,
+    Tokenstream,
+};
+
+pub struct DimensionParselet {}
+
+impl PrefixParselet for DimensionParselet {
+    fn parse(&self, _tokenstream: &mut Tokenstream, parser: &Parser, token: Token, _nesting: usize) -> Expression {
+        use Expression::*;
+
+        let name = token.value.to_owned();
+
+        if !name.chars().all(|c| c.is_alphanumeric() || c == '_') || name.is_empty() {
+            Error::InvalidDeclaration ("dimension", &format!("expected a name but found `{}`", name)).warn();
+            return Null;
+        }
+
+        if !parser.units.borrow_mut().declare_dimension(&name) {
+            Error::InvalidDeclaration (name.as_str(), "dimension already declared").warn();
+            return Null;
+        }
+
+        Dimension (name)
+    }
+}
@@ -36,6 +36,19 @@ impl InfixParselet for FunctionCallParselet {
 
             let argument = parser.parse_expr(tokenstream, token.precedence() - 1, nesting + 1);
 
+            // Parse named arguments (e.g. `units: [s, N]`)
+            let argument = match (argument, tokenstream.peek()) {
+                (Identifier (name), Some (t)) if t.class == TokenClass::Colon => {
+                    tokenstream.next();
+
+                    NamedArgument {
+                        name,
+                        value: Box::new(parser.parse_expr(tokenstream, token.precedence() - 1, nesting + 1)),
+                    }
+                },
+                (argument, _) => argument,
+            };
+
             arguments.push(argument);
         }
 
@@ -2,18 +2,30 @@
 
 use super::{
     Expression,
+    FunctionCallParselet,
+    InfixParselet,
     Parser,
     PrefixParselet,
     Token,
+    TokenClass,
     Tokenstream,
 };
 
 pub struct IdentifierParselet {}
 
 impl PrefixParselet for IdentifierParselet {
-    fn parse(&self, _tokenstream: &mut Tokenstream, _parser: &Parser, token: Token, _nesting: usize) -> Expression {
+    fn parse(&self, tokenstream: &mut Tokenstream, parser: &Parser, token: Token, nesting: usize) -> Expression {
         use Expression::*;
 
-        Identifier (token.value.to_owned())
+        let identifier = Identifier (token.value.to_owned());
+
+        // Function calls bind more tightly than any operator (e.g. `2 * sqrt(4)`)
+        match tokenstream.peek() {
+            Some (t) if t.check(TokenClass::OpenParen) => {
+                tokenstream.next();
+                FunctionCallParselet {}.parse(tokenstream, parser, identifier, t, nesting)
+            },
+            _ => identifier,
+        }
     }
-}
\ No newline at end of file
+}
@@ -9,26 +9,42 @@ mod identifier_parselet;
 mod assignment_parselet;
 mod symbolic_parselet;
 mod full_symbolic_parselet;
+mod show_work_parselet;
 mod parenthesis_parselet;
 mod header_parselet;
 mod paragraph_parselet;
+mod text_parselet;
+mod vector_parselet;
+mod pragma_parselet;
+mod unit_declaration_parselet;
+mod dimension_parselet;
 
 // Infix parselets
 mod reassignment_parselet;
 mod binary_operation_parselet;
 mod function_call_parselet;
 
-use std::collections::HashMap;
+use std::{
+    cell::RefCell,
+    collections::{
+        HashMap,
+        HashSet,
+    },
+};
 
 pub use crate::{
+    Annotation,
+    BINARY_PREFIXES,
     BinaryOperation,
+    Dimensions,
     Error,
     Expression,
+    Number,
     PREFIXES,
     Token,
     TokenClass,
     Tokenstream,
-    UNITS,
+    UnitTable,
 };
 
 use prefix_parselet::PrefixParselet;
@@ -39,9 +55,22 @@ use identifier_parselet::IdentifierParselet;
 use assignment_parselet::AssignmentParselet;
 use symbolic_parselet::SymbolicParselet;
 use full_symbolic_parselet::FullSymbolicParselet;
+use show_work_parselet::ShowWorkParselet;
 use parenthesis_parselet::ParenthesisParselet;
 use header_parselet::HeaderParselet;
 use paragraph_parselet::ParagraphParselet;
+use text_parselet::TextParselet;
+use vector_parselet::VectorParselet;
+use pragma_parselet::PragmaParselet;
+use unit_declaration_parselet::UnitDeclarationParselet;
+use dimension_parselet::DimensionParselet;
+
+pub(crate) use number_parselet::{
+    annotation_value,
+    check_unit,
+    unit_value,
+};
+pub(crate) use unit_declaration_parselet::declare;
 
 use reassignment_parselet::ReassignmentParselet;
 use binary_operation_parselet::BinaryOperationParselet;
@@ -52,6 +81,16 @@ pub struct Parser {
     prefix_parselets: HashMap<TokenClass, Box<dyn PrefixParselet>>,
     infix_parselets: HashMap<TokenClass, Box<dyn InfixParselet>>,
     pub debug: bool,
+
+    /// Units known to this parser, including declared units
+    units: RefCell<UnitTable>,
+
+    /// Variables assigned so far
+    variables: RefCell<HashSet<String>>,
+
+    /// Units written after numbers with the names of variables (e.g. `m` in `2 m`
+    /// after `let m = 5 kg`), each with the line on which it is written
+    shadowed: RefCell<Vec<(usize, String)>>,
 }
 
 impl Parser {
@@ -68,10 +107,16 @@ impl Parser {
         prefix_parselets.insert(Identifier, Box::new(IdentifierParselet {}));
         prefix_parselets.insert(Symbolic, Box::new(SymbolicParselet {}));
         prefix_parselets.insert(FullSymbolic, Box::new(FullSymbolicParselet {}));
+        prefix_parselets.insert(ShowWork, Box::new(ShowWorkParselet {}));
         prefix_parselets.insert(Let, Box::new(AssignmentParselet {}));
         prefix_parselets.insert(OpenParen, Box::new(ParenthesisParselet {}));
         prefix_parselets.insert(Header, Box::new(HeaderParselet {}));
         prefix_parselets.insert(Paragraph, Box::new(ParagraphParselet {}));
+        prefix_parselets.insert(Text, Box::new(TextParselet {}));
+        prefix_parselets.insert(OpenBracket, Box::new(VectorParselet {}));
+        prefix_parselets.insert(Pragma, Box::new(PragmaParselet {}));
+        prefix_parselets.insert(UnitDeclaration, Box::new(UnitDeclarationParselet {}));
+        prefix_parselets.insert(Dimension, Box::new(DimensionParselet {}));
 
         // Declarative grammar: infix parselet
         infix_parselets.insert(Assignment, Box::new(ReassignmentParselet {}));
@@ -85,17 +130,34 @@ impl Parser {
             prefix_parselets,
             infix_parselets,
             debug,
+            units: RefCell::new(UnitTable::default()),
+            variables: RefCell::new(HashSet::new()),
+            shadowed: RefCell::new(Vec::new()),
         }
     }
 
+    /// Gets the units written after numbers that have the names of variables,
+    /// each with the line on which it is written.
+    pub fn shadowed(&self) -> Vec<(usize, String)> {
+        self.shadowed.borrow().to_owned()
+    }
+
     /// Parses a tokenstream.
     pub fn parse(&self, input: &str) -> Vec<Expression> {
+        self.parse_located(input)
+            .into_iter()
+            .map(|(_, e)| e)
+            .collect::<Vec<Expression>>()
+    }
+
+    /// Parses a tokenstream, pairing each expression with the line on which it begins.
+    pub fn parse_located(&self, input: &str) -> Vec<(usize, Expression)> {
         let mut expressions = Vec::new();
-        let mut tokenstream = Tokenstream::from(input, self.debug);
+        let mut tokenstream = Tokenstream::from(input, self.debug, &self.units.borrow());
 
-        while let Some(_) = tokenstream.peek() {
+        while let Some (t) = tokenstream.peek() {
             let expr = self.parse_expr(&mut tokenstream, 0, 0);
-            expressions.push(expr);
+            expressions.push((t.line, expr));
         }
 
         expressions
@@ -1,176 +1,584 @@
 //! Defines a numerical value parselet.
 
-use std::collections::HashMap;
+use crate::dimension::{
+    BASE_DIMENSIONS,
+    QUANTITIES,
+};
 
 use super::{
+    BINARY_PREFIXES,
+    BinaryOperation,
+    Dimensions,
     Error,
     Expression,
+    Number,
     Parser,
     PREFIXES,
     PrefixParselet,
     Token,
     TokenClass,
     Tokenstream,
-    UNITS,
+    UnitTable,
 };
 
 /// Checks if a string is in the form of a unit.
-fn check_unit(input: &str) -> bool {
-    for (unit, _value) in UNITS {
-        if input.starts_with(unit) {
-            return true;
-        }
+pub(crate) fn check_unit(input: &str, units: &UnitTable) -> bool {
+    unit_value(input, units).is_some()
+}
+
+/// Splits a unit into its prefix multiplier, its base unit, and its exponent
+/// (e.g. `km2` or `s_1`).
+/// 
+/// Whole units take precedence over prefixed units (so `Pa` is a pascal and `mol`
+/// is a mole), and longer prefixes take precedence over shorter ones (so `dam`
+/// is a decameter).  US customary units never take a prefix (so `min` is not
+/// a milli-inch).  Binary prefixes only apply to units of information (so `MiB`
+/// is a mebibyte).
+fn match_unit<'a>(input: &str, units: &'a UnitTable) -> Option<(f64, &'a Expression, f64)> {
+    // Split the exponent from the end of the unit
+    let alpha = input.trim_end_matches(|c: char| c.is_ascii_digit() || c == '_');
+    let exp = match &input[alpha.len()..] {
+        "" => 1.0,
+        e => match e.strip_prefix('_') {
+            Some (n) if !n.contains('_') => -str::parse::<f64>(n).ok()?,
+            None => str::parse::<f64>(e).ok()?,
+            _ => return None,
+        },
+    };
+
+    if let Some (u) = units.get(alpha) {
+        return Some ((1.0, u, exp));
     }
 
-    for (prefix, _value) in PREFIXES {
-        if input.starts_with(prefix) {
-            return true;
+    let mut prefixes = PREFIXES.iter()
+        .map(|p| (p, false))
+        .chain(BINARY_PREFIXES.iter().map(|p| (p, true)))
+        .collect::<Vec<(&(&str, f64), bool)>>();
+    prefixes.sort_by_key(|((p, _), _)| std::cmp::Reverse (p.chars().count()));
+
+    for ((prefix, value), binary) in prefixes {
+        let prefixable = |a: &&str| match binary {
+            true => units.binary_prefixable(a),
+            false => units.prefixable(a),
+        };
+
+        if let Some (u) = alpha.strip_prefix(prefix).filter(prefixable).and_then(|a| units.get(a)) {
+            return Some ((*value, u, exp));
         }
     }
 
-    return false;
+    None
 }
 
-/// Gets the alpha and numeric parts of a unit.
-fn split_string(input: &str) -> (String, f64) {
-    let mut alpha = String::new();
-    let mut numeric = String::new();
-    let mut sign = 1.0;
-
-    for c in input.chars() {
-        if ('A'..='Z').contains(&c) || ('a'..='z').contains(&c) {
-            alpha.push(c);
-        } else if ('0'..='9').contains(&c) {
-            numeric.push(c);
-        } else if c == '_' {
-            sign = -1.0;
-        }
-    }
-
-    let n = if numeric.len() == 0 {
-        1.0
-    } else {
-        match str::parse::<f64>(&numeric) {
-            Ok (f) => sign * f,
-            Err (_) => {
-                Error::CouldNotParseNumber (&numeric).warn();
-                return (String::new(), 0.0);
-            },
-        }
-    };
+/// Parses a string into a unit.
+pub(crate) fn parse_unit(input: &str, units: &UnitTable) -> Expression {
+    match unit_value(input, units) {
+        Some (u) => u,
+        None => {
+            Error::UnrecognizedUnit (input).warn();
+            Expression::dimensionless(Number::exact(0.0))
+        },
+    }
+}
 
-    (alpha, n)
+/// Gets the value of a unit (e.g. `1 kN`), if the string is a unit.
+pub(crate) fn unit_value(input: &str, units: &UnitTable) -> Option<Expression> {
+    UnitParser::from(input, units).parse()
 }
 
-/// Parses a string into a unit.
-fn parse_unit(mut input: &str) -> (f64, f64, f64, f64, f64, f64, f64) {
-    let prefixes = HashMap::from(PREFIXES);
-    let units = HashMap::from(UNITS);
-
-    // Strips the prefix, if it exists
-    let (al, _) = split_string(&input);
-    let mut multiplier = if let Some(_) = units.get(&*al) {
-        1.0
-    } else {
-        match prefixes.get(&input.chars().nth(0).unwrap()) {
-            Some (p) => {
-                let mut inputchars = input.chars();
-                inputchars.next();
-                input = inputchars.as_str();
-                *p
-            },
-            None => 1.0,
+/// Gets the names of the units written in a unit expression (e.g. `m` and `L` in `m/L`),
+/// or nothing if the string is not a unit.
+pub(crate) fn unit_names(input: &str, units: &UnitTable) -> Vec<String> {
+    let mut parser = UnitParser::from(input, units);
+
+    match parser.parse() {
+        Some (_) => parser.names,
+        None => Vec::new(),
+    }
+}
+
+/// Gets a value with the dimensions of an annotation (e.g. `Force / [length]^2`),
+/// if the string is an annotation.
+pub(crate) fn annotation_value(input: &str, units: &UnitTable) -> Option<Expression> {
+    let input = input.split_whitespace().collect::<String>();
+    let mut parser = UnitParser::from(&input, units);
+    parser.dimensions = true;
+    parser.parse()
+}
+
+/// Unicode superscript digits, in order.
+const SUPERSCRIPTS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+/// Parses unit expressions such as `m/s^2`, `kg·m²`, `(kg*m)/s^-2`, or `s_2`.
+/// 
+/// The grammar is
+/// ```text
+/// expression := term (('*' | '·' | '/') term)*
+/// term := atom (('^' exponent) | superscript)?
+/// atom := '(' expression ')' | unit
+/// ```
+/// where division is left-associative (`m/s*kg` is `m kg / s`) and a unit
+/// may carry a legacy exponent (`m2`, `s_2`).
+/// 
+/// When parsing dimensions, an atom may also be a named quantity (e.g. `Pressure`)
+/// or a base dimension in brackets (e.g. `[length]` or `[currency]`).
+struct UnitParser<'a> {
+    chars: Vec<char>,
+    index: usize,
+    units: &'a UnitTable,
+
+    /// Whether quantities and base dimensions are allowed
+    dimensions: bool,
+
+    /// Names of the units parsed so far, as written
+    names: Vec<String>,
+}
+
+impl<'a> UnitParser<'a> {
+    /// Constructs a new unit parser from an input string.
+    fn from(input: &str, units: &'a UnitTable) -> Self {
+        Self {
+            chars: input.chars().collect::<Vec<char>>(),
+            index: 0,
+            units,
+            dimensions: false,
+            names: Vec::new(),
         }
-    };
+    }
 
-    let (alpha, exp) = split_string(&input);
-
-    let (
-        mult,
-        mut kg,
-        mut m,
-        mut s,
-        mut a,
-        mut k,
-        mut mol,
-    ) = match units.get(&*alpha) {
-        Some (u) => *u,
-        None => {
-            Error::CouldNotParseNumber (&input).warn();
-            return (0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
-        },
-    };
+    /// Peeks at the next character.
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    /// Consumes the next character if it is the expected character.
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some (c) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Parses the whole input, returning `None` if it is not a unit.
+    fn parse(&mut self) -> Option<Expression> {
+        let unit = self.expression()?;
+
+        if self.index == self.chars.len() {
+            Some (unit)
+        } else {
+            None
+        }
+    }
+
+    /// Parses a product or quotient of terms.
+    fn expression(&mut self) -> Option<Expression> {
+        let mut unit = self.term()?;
+
+        loop {
+            if self.eat('*') || self.eat('·') {
+                unit = BinaryOperation::Mul.simplify(&unit, &self.term()?);
+            } else if self.eat('/') {
+                unit = BinaryOperation::Div.simplify(&unit, &self.term()?);
+            } else {
+                return Some (unit);
+            }
+        }
+    }
+
+    /// Parses an atom raised to an optional power.
+    fn term(&mut self) -> Option<Expression> {
+        let unit = self.atom()?;
+
+        if self.eat('^') {
+            let exp = if self.eat('(') {
+                let exp = self.number()?;
+                self.eat(')').then_some(exp)?
+            } else {
+                self.number()?
+            };
+
+            return Some (raise(&unit, exp));
+        }
+
+        match self.superscript() {
+            Some (exp) => Some (raise(&unit, exp)),
+            None => Some (unit),
+        }
+    }
+
+    /// Parses a parenthesized expression or a single unit.
+    fn atom(&mut self) -> Option<Expression> {
+        if self.eat('(') {
+            let unit = self.expression()?;
+            return self.eat(')').then_some(unit);
+        }
+
+        let bracket = self.dimensions && self.eat('[');
+        let start = self.index;
+
+        while let Some (c) = self.peek() {
+            if c.is_alphabetic() || c.is_ascii_digit() || ['_', '%', '‰'].contains(&c) {
+                self.index += 1;
+            } else {
+                break;
+            }
+        }
+
+        let name = self.chars[start..self.index].iter().collect::<String>();
+
+        if bracket {
+            return self.eat(']').then(|| self.base_dimension(&name))?;
+        }
+
+        let quantity = QUANTITIES.iter().find(|(q, _)| *q == name);
+
+        if let (true, Some ((_, (_, kg, m, s, a, k, mol, cd)))) = (self.dimensions, quantity) {
+            return Some (Expression::Float {
+                value: Number::exact(1.0),
+                kg: *kg,
+                m: *m,
+                s: *s,
+                a: *a,
+                k: *k,
+                mol: *mol,
+                cd: *cd,
+                other: Dimensions::new(),
+            });
+        }
 
-    // Correct the multiplier for conversions (e.g. kg <-> g)
-    multiplier *= mult;
-    multiplier = multiplier.powf(exp);
+        let (prefix, unit, exp) = match_unit(&name, self.units)?;
+        let unit = BinaryOperation::Mul.simplify(&Expression::dimensionless(Number::exact(prefix)), unit);
+        self.names.push(name);
 
-    kg *= exp;
-    m *= exp;
-    s *= exp;
-    a *= exp;
-    k *= exp;
-    mol *= exp;
+        Some (raise(&unit, exp))
+    }
+
+    /// Gets a value with a base dimension (e.g. `length` or a declared dimension).
+    fn base_dimension(&self, name: &str) -> Option<Expression> {
+        let mut powers = [0.0; 7];
+        let mut other = Dimensions::new();
+
+        match BASE_DIMENSIONS.iter().position(|d| *d == name) {
+            Some (i) => powers[i] = 1.0,
+            None if self.units.has_dimension(name) => other = Dimensions::base(name),
+            None => return None,
+        }
+
+        let [kg, m, s, a, k, mol, cd] = powers;
+
+        Some (Expression::Float {
+            value: Number::exact(1.0),
+            kg,
+            m,
+            s,
+            a,
+            k,
+            mol,
+            cd,
+            other,
+        })
+    }
+
+    /// Parses a (possibly negative or fractional) number.
+    fn number(&mut self) -> Option<f64> {
+        let start = self.index;
+
+        self.eat('-');
+
+        while let Some (c) = self.peek() {
+            if c.is_ascii_digit() || c == '.' {
+                self.index += 1;
+            } else {
+                break;
+            }
+        }
+
+        let number = self.chars[start..self.index].iter().collect::<String>();
+        str::parse::<f64>(&number).ok()
+    }
+
+    /// Parses an exponent written in Unicode superscripts (e.g. `⁻²`).
+    fn superscript(&mut self) -> Option<f64> {
+        let start = self.index;
+        let sign = if self.eat('⁻') { -1.0 } else { 1.0 };
+        let mut exp = None;
+
+        while let Some (d) = self.peek().and_then(|c| SUPERSCRIPTS.iter().position(|s| *s == c)) {
+            self.index += 1;
+            exp = Some (exp.unwrap_or(0.0) * 10.0 + d as f64);
+        }
 
-    (multiplier, kg, m, s, a, k, mol)
+        if exp.is_none() {
+            self.index = start;
+        }
+
+        exp.map(|e| sign * e)
+    }
+}
+
+/// Raises a unit to a power.
+fn raise(unit: &Expression, exp: f64) -> Expression {
+    match unit {
+        Expression::Float {
+            value,
+            kg,
+            m,
+            s,
+            a,
+            k,
+            mol,
+            cd,
+            other,
+        } => Expression::Float {
+            value: value.pow(exp),
+            kg: kg * exp,
+            m: m * exp,
+            s: s * exp,
+            a: a * exp,
+            k: k * exp,
+            mol: mol * exp,
+            cd: cd * exp,
+            other: other.scale(exp),
+        },
+        u => u.to_owned(),
+    }
 }
 
 pub struct NumberParselet {}
 
 impl PrefixParselet for NumberParselet {
-    fn parse(&self, tokenstream: &mut Tokenstream, _parser: &Parser, mut token: Token, _nesting: usize) -> Expression {
-        use Expression::*;
-
+    fn parse(&self, tokenstream: &mut Tokenstream, parser: &Parser, mut token: Token, _nesting: usize) -> Expression {
         // Parse negative sign
-        let sign = if token.check(TokenClass::Minus) {
+        let negative = if token.check(TokenClass::Minus) {
             token = tokenstream.next_unwrap();
-            -1.0
+            true
         } else {
-            1.0
+            false
         };
 
-        let mut multiplier = 1.0;
-        let mut kilogram = 0.0;
-        let mut meter = 0.0;
-        let mut second = 0.0;
-        let mut amp = 0.0;
-        let mut kelvin = 0.0;
-        let mut mole = 0.0;
+        let units = parser.units.borrow();
+        let mut unit = Expression::dimensionless(Number::exact(1.0));
+        let mut names = Vec::new();
 
         while let Some(t) = tokenstream.peek() {
-            if t.check(TokenClass::Identifier) && check_unit(&t.value) {
+            if t.check(TokenClass::Unit) {
                 tokenstream.next();
-                
-                let (mult, kg, m, s, a, k, mol) = parse_unit(&t.value);
-
-                multiplier *= mult;
-                kilogram += kg;
-                meter += m;
-                second += s;
-                amp += a;
-                kelvin += k;
-                mole += mol;
+
+                // Units take precedence over variables after a number
+                for name in unit_names(&t.value, &units) {
+                    if parser.variables.borrow().contains(&name) {
+                        Error::ShadowedUnit (&t.line.to_string(), &name).warn();
+                        parser.shadowed.borrow_mut().push((t.line, name));
+                    }
+                }
+
+                unit = BinaryOperation::Mul.simplify(&unit, &parse_unit(&t.value, &units));
+                names.push(t.value);
             } else {
                 break;
             }
         }
 
-        let value = match str::parse::<f64>(&token.value) {
-            Ok (f) => f,
+        let value = match Number::parse(&token.value) {
+            Some (n) => n,
             _ => {
                 Error::CouldNotParseNumber (&token.value).warn();
-                0.0
+                Number::exact(0.0)
             },
         };
 
-        Float {
-            value: multiplier * sign * value,
-            kg: kilogram,
-            m: meter,
-            s: second,
-            a: amp,
-            k: kelvin,
-            mol: mole,
+        let value = Expression::dimensionless(if negative {
+            -value
+        } else {
+            value
+        });
+
+        // Dimensionless units are kept for display (e.g. `5 %` or `2 mm/mm`)
+        match names.join(" ").as_str() {
+            "" => value,
+            "dB" => Expression::Ratio {
+                value: Box::new(decibels(&value)),
+                unit: "dB".to_string(),
+            },
+            n if unit.is_dimensionless() => Expression::Ratio {
+                value: Box::new(BinaryOperation::Mul.simplify(&value, &unit)),
+                unit: n.to_string(),
+            },
+            _ => BinaryOperation::Mul.simplify(&value, &unit),
+        }
+    }
+}
+
+/// Converts a number of decibels to a power ratio (so `10 dB` is 10).
+fn decibels(value: &Expression) -> Expression {
+    match value {
+        Expression::Float { value, .. } => Expression::dimensionless(Number::from(10f64.powf(value.to_f64() / 10.0))),
+        v => v.to_owned(),
+    }
+}
+#[test]
+fn test_units() {
+    let units = UnitTable::default();
+    let close = |a: f64, b: f64| (a - b).abs() <= 1E-9 * a.abs().max(b.abs());
+    let unit = |input: &str| match parse_unit(input, &units) {
+        Expression::Float { value, kg, m, s, a, k, mol, cd, .. } => (value.to_f64(), (kg, m, s, a, k, mol, cd)),
+        _ => panic!("{} is not a unit", input),
+    };
+
+    // Every prefix with every unit, with and without an exponent
+    for (prefix, p) in PREFIXES {
+        for (name, (mult, kg, m, s, a, k, mol, cd)) in crate::UNITS {
+            for (suffix, exp) in [("", 1.0), ("2", 2.0), ("_1", -1.0)] {
+                let input = format!("{}{}{}", prefix, name, suffix);
+                let (value, dimensions) = unit(&input);
+
+                assert!(check_unit(&input, &units), "{}", input);
+                assert!(close(value, (p * mult).powf(exp)), "{}", input);
+                assert_eq!(
+                    dimensions,
+                    (kg * exp, m * exp, s * exp, a * exp, k * exp, mol * exp, cd * exp),
+                    "{}",
+                    input,
+                );
+            }
         }
     }
-}
\ No newline at end of file
+
+    // Whole units take precedence over prefixes, and longer prefixes over shorter ones
+    assert!(close(unit("Pa").0, 1.0));
+    assert_eq!(unit("mol").1.5, 1.0);
+    assert!(close(unit("dam").0, 10.0));
+    assert!(close(unit("PPa").0, 1E15));
+    assert!(close(unit("µm").0, 1E-6));
+    assert!(close(unit("μm").0, 1E-6));
+
+    // Unit expressions
+    for (input, value, dimensions) in [
+        ("m/s^2", 1.0, (0.0, 1.0, -2.0)),
+        ("kg*m^2", 1.0, (1.0, 2.0, 0.0)),
+        ("kg·m²/s²", 1.0, (1.0, 2.0, -2.0)),
+        ("g/(m*s^2)", 1E-3, (1.0, -1.0, -2.0)),
+        ("km/s^-1", 1E3, (0.0, 1.0, 1.0)),
+        ("mm^(-2)", 1E6, (0.0, -2.0, 0.0)),
+        ("ms⁻¹", 1E3, (0.0, 0.0, -1.0)),
+        ("m/s*kg", 1.0, (1.0, 1.0, -1.0)),
+    ] {
+        let (v, d) = unit(input);
+        assert!(close(v, value), "{}", input);
+        assert_eq!((d.0, d.1, d.2), dimensions, "{}", input);
+    }
+
+    // Derived units, with Unicode symbols
+    assert_eq!(unit("kΩ"), unit("kO"));
+    assert_eq!(unit("Ω"), unit("V/A"));
+    assert_eq!(unit("S"), unit("A/V"));
+    assert_eq!(unit("T"), unit("Wb/m^2"));
+    assert_eq!(unit("lx").1, unit("cd/m^2").1);
+    assert_eq!(unit("mcd").1.6, 1.0);
+
+    // US customary units
+    assert!(close(unit("kip").0, 4448.2216152605));
+    assert!(close(unit("lbf*in").0, 4.4482216152605 * 0.0254));
+
+    // Dimensionless units
+    assert!(close(unit("%").0, 0.01));
+    assert!(close(unit("ppm").0, 1E-6));
+    assert_eq!(unit("mm/mm").1, (0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0));
+    assert!(!check_unit("kppm", &units));
+
+    // Units of information, with decimal and binary prefixes
+    let bits = |input: &str| match parse_unit(input, &units) {
+        Expression::Float { value, other, .. } => {
+            assert_eq!(other, Dimensions::base(crate::INFORMATION), "{}", input);
+            value.to_f64()
+        },
+        _ => panic!("{} is not a unit", input),
+    };
+
+    assert!(close(bits("B"), 8.0));
+    assert!(close(bits("kB"), 8E3));
+    assert!(close(bits("MiB"), 8.0 * 1048576.0));
+    assert!(close(bits("Gibit"), 1073741824.0));
+    assert!(close(unit("Mbit/s").0, 1E6));
+    assert!(close(unit("dB").0, 1.0));
+    assert!(!check_unit("Kim", &units));
+    assert!(!check_unit("KiB2x", &units));
+
+    // Units as values
+    assert!(close(unit("kN").0, 1E3));
+    assert!(unit_value("x", &units).is_none());
+
+    // Annotations
+    for (input, dimensions) in [
+        ("Pressure", (1.0, -1.0, -2.0)),
+        ("[length]", (0.0, 1.0, 0.0)),
+        ("Force / [length]^2", (1.0, -1.0, -2.0)),
+        ("[mass]*Acceleration", (1.0, 1.0, -2.0)),
+        ("mm", (0.0, 1.0, 0.0)),
+    ] {
+        match annotation_value(input, &units) {
+            Some (Expression::Float { kg, m, s, .. }) => assert_eq!((kg, m, s), dimensions, "{}", input),
+            _ => panic!("{} is not an annotation", input),
+        }
+    }
+
+    assert!(annotation_value("[currency]", &units).is_none());
+    assert!(unit_value("Pressure", &units).is_none());
+
+    // Identifiers that are not units
+    for input in ["sqrt", "mean", "min", "kin", "na", "x", "ms_", "m_1_2", "kk", "m/", "(m", "m^", "m*x"] {
+        assert!(!check_unit(input, &units), "{}", input);
+    }
+}
+
+#[test]
+fn test_unit_expressions() {
+    let parser = Parser::new(false);
+    let mut env = crate::Environment::new();
+
+    // Unit expressions apply to the number before them
+    let output = env.evaluate(&parser.parse("9.81 m/s^2\n1 kg·m²/s²\n2 (kg*m)/s^2\n3 m s_2\nlet a = 2 m^2*4\n"));
+    assert_eq!(output, "9.8100 m s^-2\n1.0000 J\n2.0000 N\n3.0000 m s^-2\na = 8.0000 m^2\n");
+}
+
+#[test]
+fn test_dimensionless_units() {
+    let parser = Parser::new(false);
+    let ratio = |input: &str| match &parser.parse(input)[0] {
+        Expression::Ratio { value, unit } => match value.as_ref() {
+            Expression::Float { value, .. } => (value.to_f64(), unit.to_owned()),
+            _ => panic!("expected a float"),
+        },
+        _ => panic!("{} is not a ratio", input),
+    };
+
+    // Dimensionless units keep their display unit, and decibels are power ratios
+    assert_eq!(ratio("5 %"), (0.05, "%".to_string()));
+    assert_eq!(ratio("12 ppm"), (12E-6, "ppm".to_string()));
+    assert_eq!(ratio("2 mm/mm"), (2.0, "mm/mm".to_string()));
+    assert_eq!(ratio("20 dB"), (100.0, "dB".to_string()));
+}
+
+#[test]
+fn test_shadowed_units() {
+    // Common variable names that are also units stay quiet
+    let parser = Parser::new(false);
+    parser.parse("let g = 9.81 m/s^2\nlet L = 3 m\nlet T = 4 s\nlet m = 5 kg\nlet g_0 = g\nlet F_g = m * g\nlet s = L / T\nlet F = F_g * 2\n");
+    assert!(parser.shadowed().is_empty());
+
+    // A variable written after a number is read as a unit
+    parser.parse("let x = 2 m\n");
+    assert_eq!(parser.shadowed(), [(1, "m".to_string())]);
+
+    // Variables are found within unit expressions
+    let parser = Parser::new(false);
+    parser.parse("let L = 2 m\nlet q = 10 m/L\n");
+    assert_eq!(parser.shadowed(), [(2, "L".to_string())]);
+
+    // A variable is not yet defined in its own definition
+    let parser = Parser::new(false);
+    parser.parse("let m = 1 B * 2 m\n");
+    assert!(parser.shadowed().is_empty());
+}
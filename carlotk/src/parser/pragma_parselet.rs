@@ -0,0 +1,24 @@
+//! Defines a pragma parselet.
+
+use super::{
+    Expression,
+    Parser,
+    PrefixParselet,
+    Token,
+    Tokenstream,
+};
+
+pub struct PragmaParselet {}
+
+impl PrefixParselet for PragmaParselet {
+    fn parse(&self, _tokenstream: &mut Tokenstream, _parser: &Parser, token: Token, _nesting: usize) -> Expression {
+        use Expression::*;
+
+        let mut words = token.value.split_whitespace().map(|w| w.to_string());
+
+        Pragma {
+            name: words.next().unwrap_or_default(),
+            arguments: words.collect::<Vec<String>>(),
+        }
+    }
+}
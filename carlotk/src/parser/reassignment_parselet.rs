@@ -27,6 +27,7 @@ impl InfixParselet for ReassignmentParselet {
         Reassignment {
             left: left.to_owned(),
             right: Box::new(right),
+            line: token.line,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,22 @@
+//! Defines a show-work parselet.
+
+use super::{
+    Expression,
+    Parser,
+    PrefixParselet,
+    Token,
+    TokenClass,
+    Tokenstream,
+};
+
+pub struct ShowWorkParselet {}
+
+impl PrefixParselet for ShowWorkParselet {
+    fn parse(&self, tokenstream: &mut Tokenstream, _parser: &Parser, _token: Token, _nesting: usize) -> Expression {
+        use Expression::*;
+
+        let variable = &tokenstream.get(TokenClass::Identifier).value;
+
+        ShowWork (variable.to_owned())
+    }
+}
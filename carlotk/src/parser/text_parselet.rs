@@ -0,0 +1,19 @@
+//! Defines a text (string literal) parselet.
+
+use super::{
+    Expression,
+    Parser,
+    PrefixParselet,
+    Token,
+    Tokenstream,
+};
+
+pub struct TextParselet {}
+
+impl PrefixParselet for TextParselet {
+    fn parse(&self, _tokenstream: &mut Tokenstream, _parser: &Parser, token: Token, _nesting: usize) -> Expression {
+        use Expression::*;
+
+        Text (token.value.to_owned())
+    }
+}
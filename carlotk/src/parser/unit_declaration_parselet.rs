@@ -0,0 +1,135 @@
+//! Defines a unit declaration parselet.
+
+use super::{
+    BinaryOperation,
+    Error,
+    Expression,
+    Number,
+    Parser,
+    PrefixParselet,
+    Token,
+    Tokenstream,
+    UnitTable,
+    unit_value,
+};
+
+/// Characters that join the parts of a unit definition without implying multiplication.
+const OPERATORS: [char; 5] = ['*', '·', '/', '^', '('];
+
+/// Evaluates the definition of a unit, such as `1000 lbf` or `kip / inch^2`.
+/// Spaces around operators are ignored, and other spaces multiply.
+fn definition(input: &str, units: &UnitTable) -> Option<Expression> {
+    let mut words = input.split_whitespace().peekable();
+
+    // Optional numeric factor
+    let factor = match words.peek().and_then(|w| Number::parse(w)) {
+        Some (n) => {
+            words.next();
+            n
+        },
+        None => Number::exact(1.0),
+    };
+
+    let mut unit = String::new();
+
+    for word in words {
+        let joined = unit.is_empty()
+            || unit.ends_with(OPERATORS)
+            || word.starts_with(OPERATORS)
+            || word.starts_with(')');
+
+        if !joined {
+            unit.push('*');
+        }
+
+        unit.push_str(word);
+    }
+
+    let factor = Expression::dimensionless(factor);
+
+    if unit.is_empty() {
+        Some (factor)
+    } else {
+        Some (BinaryOperation::Mul.simplify(&factor, &unit_value(&unit, units)?))
+    }
+}
+
+/// Declares a unit from the statement of a unit declaration (e.g. `kip = 1000 lbf`),
+/// defining it in a table of units and returning its name and value (or `None` if it
+/// is the base unit of a dimension), or else the subject and reason of the error.
+pub(crate) fn declare(statement: &str, units: &mut UnitTable) -> Result<(String, Option<Expression>), (String, String)> {
+    let (name, value) = match statement.split_once('=') {
+        Some ((n, v)) => (n.trim(), Some (v.trim())),
+        None => (statement.trim(), None),
+    };
+
+    if !name.chars().all(|c| c.is_alphabetic()) || name.is_empty() {
+        return Err (("unit".to_string(), format!("expected a name but found `{}`", name)));
+    }
+
+    let value = match value {
+        Some (v) => match definition(v, units) {
+            Some (d) => {
+                units.define(name, d.to_owned());
+                Some (d)
+            },
+            None => return Err ((name.to_string(), format!("could not evaluate `{}`", v))),
+        },
+        None => match units.define_base(name) {
+            Some (_) => None,
+            None => return Err ((name.to_string(), "expected a value or a new dimension".to_string())),
+        },
+    };
+
+    Ok ((name.to_string(), value))
+}
+
+pub struct UnitDeclarationParselet {}
+
+impl PrefixParselet for UnitDeclarationParselet {
+    fn parse(&self, _tokenstream: &mut Tokenstream, parser: &Parser, token: Token, _nesting: usize) -> Expression {
+        match declare(&token.value, &mut parser.units.borrow_mut()) {
+            Ok ((name, value)) => Expression::UnitDeclaration {
+                name,
+                value: value.map(Box::new),
+            },
+            Err ((subject, reason)) => {
+                Error::InvalidDeclaration (subject.as_str(), reason.as_str()).warn();
+                Expression::Null
+            },
+        }
+    }
+}
+
+#[test]
+fn test_declare() {
+    let mut units = UnitTable::default();
+    let value = |result: Result<(String, Option<Expression>), (String, String)>| match result {
+        Ok ((_, Some (Expression::Float { value, kg, m, s, .. }))) => (value.to_f64(), (kg, m, s)),
+        _ => panic!("expected a unit"),
+    };
+
+    // Spaces multiply, except around operators
+    let (kip, dimensions) = value(declare("kip = 1000 lbf", &mut units));
+    assert!((kip - 4448.2216152605).abs() < 1E-9);
+    assert_eq!(dimensions, (1.0, 1.0, -2.0));
+
+    let (ksi, dimensions) = value(declare("ksi = kip / in^2", &mut units));
+    assert!((ksi - 4448.2216152605 / 0.0254 / 0.0254).abs() < 1E-6);
+    assert_eq!(dimensions, (1.0, -1.0, -2.0));
+
+    assert_eq!(value(declare("kNm = kN m", &mut units)).1, (1.0, 2.0, -2.0));
+
+    // Declared units are available to later declarations
+    assert!(units.contains("ksi"));
+
+    // Invalid declarations
+    assert!(declare("k ip = 1000 lbf", &mut units).is_err());
+    assert!(declare("bad = 3 zzz", &mut units).is_err());
+    assert!(!units.contains("bad"));
+    assert!(declare("USD", &mut units).is_err());
+
+    // Base units of declared dimensions
+    units.declare_dimension("currency");
+    assert!(matches!(declare("USD", &mut units), Ok ((_, None))));
+}
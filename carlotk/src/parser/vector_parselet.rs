@@ -0,0 +1,38 @@
+//! Defines a vector parselet.
+
+use super::{
+    Expression,
+    Parser,
+    PrefixParselet,
+    Token,
+    TokenClass,
+    Tokenstream,
+};
+
+pub struct VectorParselet {}
+
+impl PrefixParselet for VectorParselet {
+    fn parse(&self, tokenstream: &mut Tokenstream, parser: &Parser, _token: Token, nesting: usize) -> Expression {
+        use Expression::*;
+
+        // Set up list of elements
+        let mut elements = Vec::new();
+
+        while let Some (t) = tokenstream.peek() {
+            // Break on closing bracket
+            if t.class == TokenClass::CloseBracket {
+                tokenstream.next();
+                break;
+            } else if t.class == TokenClass::Comma {
+                tokenstream.next();
+                continue;
+            }
+
+            let element = parser.parse_expr(tokenstream, 0, nesting + 1);
+
+            elements.push(element);
+        }
+
+        Vector (elements)
+    }
+}
@@ -0,0 +1,195 @@
+//! Renders values and units with the macros of the LaTeX `siunitx` package
+//! (e.g. `\qty{98.1}{\kilo\newton\per\metre\squared}`).
+
+use crate::expression::UnitPart;
+
+/// Defines the `siunitx` macro of each unit with one.
+/// Units without a macro (e.g. `psi`) are written literally.
+const UNITS: [(&str, &str); 33] = [
+    ("g", "\\gram"),
+    ("m", "\\metre"),
+    ("s", "\\second"),
+    ("N", "\\newton"),
+    ("Pa", "\\pascal"),
+    ("L", "\\litre"),
+    ("Hz", "\\hertz"),
+    ("J", "\\joule"),
+    ("W", "\\watt"),
+    ("A", "\\ampere"),
+    ("C", "\\coulomb"),
+    ("V", "\\volt"),
+    ("Ω", "\\ohm"),
+    ("Ω", "\\ohm"),
+    ("O", "\\ohm"),
+    ("S", "\\siemens"),
+    ("F", "\\farad"),
+    ("Wb", "\\weber"),
+    ("T", "\\tesla"),
+    ("H", "\\henry"),
+    ("K", "\\kelvin"),
+    ("mol", "\\mole"),
+    ("kat", "\\katal"),
+    ("cd", "\\candela"),
+    ("lm", "\\lumen"),
+    ("lx", "\\lux"),
+    ("Bq", "\\becquerel"),
+    ("Gy", "\\gray"),
+    ("Sv", "\\sievert"),
+    ("bit", "\\bit"),
+    ("B", "\\byte"),
+    ("%", "\\percent"),
+    ("percent", "\\percent"),
+];
+
+/// Defines the `siunitx` macro of each prefix.
+const PREFIXES: [(&str, &str); 22] = [
+    ("a", "\\atto"),
+    ("f", "\\femto"),
+    ("p", "\\pico"),
+    ("n", "\\nano"),
+    ("u", "\\micro"),
+    ("µ", "\\micro"),
+    ("μ", "\\micro"),
+    ("m", "\\milli"),
+    ("c", "\\centi"),
+    ("d", "\\deci"),
+    ("da", "\\deca"),
+    ("h", "\\hecto"),
+    ("k", "\\kilo"),
+    ("M", "\\mega"),
+    ("G", "\\giga"),
+    ("T", "\\tera"),
+    ("P", "\\peta"),
+    ("E", "\\exa"),
+    ("Ki", "\\kibi"),
+    ("Mi", "\\mebi"),
+    ("Gi", "\\gibi"),
+    ("Ti", "\\tebi"),
+];
+
+/// Gets the macro of a unit or prefix, if it has one.
+fn lookup(table: &[(&str, &'static str)], name: &str) -> Option<&'static str> {
+    table.iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, m)| *m)
+}
+
+/// Gets the macros of a unit, which may itself be prefixed (e.g. `\kilo\gram` for `kg`
+/// or `\mega\pascal` for `MPa` in a unit system).
+fn unit_macro(name: &str) -> Option<String> {
+    if let Some (m) = lookup(&UNITS, name) {
+        return Some (m.to_string());
+    }
+
+    let mut prefixes = PREFIXES;
+    prefixes.sort_by_key(|(p, _)| std::cmp::Reverse (p.chars().count()));
+
+    prefixes.iter()
+        .find_map(|(p, pm)| name.strip_prefix(p)
+            .and_then(|u| lookup(&UNITS, u))
+            .map(|m| format!("{}{}", pm, m)))
+}
+
+/// Converts units, each with its prefix and power, to the argument of `\unit` or `\qty`.
+///
+/// Units with macros are written with macros (e.g. `\kilo\newton\per\metre\squared`).
+/// If any unit has no macro, all units are written literally (e.g. `lbf.in^{-1}`),
+/// since `siunitx` does not mix literal units with macros.
+pub fn units(parts: &[UnitPart]) -> String {
+    let macros = parts.iter()
+        .map(|(prefix, unit, pow)| {
+            let unit = unit_macro(unit)?;
+            let prefix = match prefix {
+                Some (p) => lookup(&PREFIXES, p)?,
+                None => "",
+            };
+
+            Some (format!("{}{}{}", if *pow < 0.0 { "\\per" } else { "" }, prefix, unit) + &power(pow.abs()))
+        })
+        .collect::<Option<Vec<String>>>();
+
+    match macros {
+        Some (m) => m.join(""),
+        None => parts.iter()
+            .map(|(prefix, unit, pow)| {
+                let prefix = match prefix {
+                    Some ("u" | "µ" | "μ") => "\\mu ",
+                    Some (p) => p,
+                    None => "",
+                };
+
+                match pow {
+                    1.0 => format!("{}{}", prefix, unit),
+                    _ => format!("{}{}^{{{}}}", prefix, unit, pow),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("."),
+    }
+}
+
+/// Converts the power of a unit to a `siunitx` macro.
+fn power(pow: f64) -> String {
+    match pow {
+        1.0 => String::new(),
+        2.0 => "\\squared".to_string(),
+        3.0 => "\\cubed".to_string(),
+        p => format!("\\tothe{{{}}}", p),
+    }
+}
+
+/// Converts a number formatted as LaTeX to the argument of `\num` or `\qty`
+/// (e.g. `1.5e3` for `1.5 \times 10^{3}`), if `siunitx` can parse it.
+/// Fractions cannot be parsed.
+pub fn number(latex: &str) -> Option<String> {
+    let number = match latex.split_once(" \\times 10^{") {
+        Some ((mantissa, exp)) => format!("{}e{}", mantissa, exp.strip_suffix('}')?),
+        None => latex.to_string(),
+    };
+
+    if number.contains('\\') {
+        None
+    } else {
+        Some (number)
+    }
+}
+
+/// Renders a value in a dimensionless unit (e.g. `\qty{5}{\percent}`),
+/// if the unit has a macro.
+pub fn ratio(latex: &str, unit: &str) -> Option<String> {
+    let unit = match unit {
+        "dB" => "\\deci\\bel",
+        u => lookup(&UNITS, u)?,
+    };
+
+    Some (format!("\\qty{{{}}}{{{}}}", number(latex)?, unit))
+}
+
+/// Renders a value with units (e.g. `\qty{98.1}{\newton}`), a dimensionless value
+/// (e.g. `\num{2.5}`), or a fraction followed by its units.
+pub fn quantity(latex: &str, parts: &[UnitPart]) -> String {
+    match (number(latex), parts.is_empty()) {
+        (Some (n), true) => format!("\\num{{{}}}", n),
+        (Some (n), false) => format!("\\qty{{{}}}{{{}}}", n, units(parts)),
+        (None, true) => latex.to_string(),
+        (None, false) => format!("{} \\; \\unit{{{}}}", latex, units(parts)),
+    }
+}
+
+#[test]
+fn test_siunitx() {
+    let part = |prefix: Option<&'static str>, unit: &str, pow: f64| (prefix, unit.to_string(), pow);
+
+    assert_eq!(quantity("98.1000", &[part(None, "N", 1.0)]), "\\qty{98.1000}{\\newton}");
+    assert_eq!(
+        quantity("1.50 \\times 10^{3}", &[part(Some ("k"), "N", 1.0), part(None, "m", -2.0)]),
+        "\\qty{1.50e3}{\\kilo\\newton\\per\\metre\\squared}",
+    );
+    assert_eq!(units(&[part(Some ("µ"), "m", 1.0), part(None, "s", -4.0)]), "\\micro\\metre\\per\\second\\tothe{4}");
+    assert_eq!(units(&[part(None, "lbf", 1.0), part(None, "in", -1.0)]), "lbf.in^{-1}");
+    assert_eq!(units(&[part(None, "kg", 1.0), part(None, "mm", -3.0)]), "\\kilo\\gram\\per\\milli\\metre\\cubed");
+    assert_eq!(quantity("\\frac{1}{3}", &[part(None, "m", 1.0)]), "\\frac{1}{3} \\; \\unit{\\metre}");
+    assert_eq!(quantity("2.5000", &[]), "\\num{2.5000}");
+    assert_eq!(ratio("5", "%"), Some ("\\qty{5}{\\percent}".to_string()));
+    assert_eq!(ratio("12", "ppm"), None);
+}
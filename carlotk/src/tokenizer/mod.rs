@@ -2,7 +2,14 @@
 
 mod token;
 
-use crate::Error;
+use crate::{
+    Error,
+    parser::{
+        check_unit,
+        declare,
+    },
+    UnitTable,
+};
 
 pub use token::{
     Token,
@@ -13,6 +20,22 @@ pub use token::{
 pub struct Charstream {
     chars: Vec<char>,
     index: usize,
+
+    /// Whether the previous token may be followed by a unit
+    unit_allowed: bool,
+
+    /// Whether the previous token may be followed by an annotation
+    /// (i.e. it is the variable of a `let` expression)
+    annotation_allowed: bool,
+
+    /// Class of the previous token
+    previous: Option<TokenClass>,
+
+    /// Current line of the input
+    line: usize,
+
+    /// Units known to this character stream, including declared units
+    units: UnitTable,
 }
 
 /// Specifies characters used to break tokens.
@@ -23,10 +46,15 @@ const WHITESPACE: [char; 3] = [' ', '\t', '\n'];
 
 impl Charstream {
     /// Constructs a new character stream from an input string.
-    pub fn from(input: &str) -> Self {
+    pub fn from(input: &str, units: &UnitTable) -> Self {
         Self {
             chars: input.chars().collect::<Vec<char>>(),
             index: 0,
+            unit_allowed: false,
+            annotation_allowed: false,
+            previous: None,
+            line: 1,
+            units: units.to_owned(),
         }
     }
 
@@ -39,12 +67,116 @@ impl Charstream {
         }
     }
 
+    /// Gets a unit (e.g. `m/s^2`) from this character stream, if one is next.
+    /// Units are written without spaces, so `2 m/s` is a speed while
+    /// `2 m / s` divides a length by `s`.
+    fn unit(&mut self) -> Option<Token> {
+        let mut value = String::new();
+        let mut depth = 0;
+
+        for c in &self.chars[self.index..] {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => break,
+                ')' => depth -= 1,
+                '*' | '·' | '/' | '^' | '-' | '.' | '_' | '⁻' | '%' | '‰' => (),
+                c if c.is_alphanumeric() => (),
+                _ => break,
+            }
+
+            value.push(*c);
+        }
+
+        // Fall back to the longest valid unit (e.g. `m^2` in `2 m^2*x`)
+        while !check_unit(&value, &self.units) {
+            value.pop()?;
+        }
+
+        self.index += value.chars().count();
+
+        // Consume a trailing token break
+        if let Some (c) = self.peek() {
+            if TOKENBREAK.contains(&c) {
+                self.next();
+            }
+        }
+
+        Some (Token {
+            class: TokenClass::Unit,
+            value,
+            line: self.line,
+        })
+    }
+
+    /// Gets an annotation (e.g. `: Pressure` in `let sigma: Pressure = F / A`)
+    /// from this character stream, if one is next.
+    fn annotation(&mut self) -> Option<Token> {
+        if self.peek() != Some (':') {
+            return None;
+        }
+
+        let line = self.line;
+        self.next();
+
+        let mut value = String::new();
+
+        while let Some (c) = self.peek() {
+            if c == '=' || c == '\n' {
+                break;
+            }
+
+            value.push(c);
+            self.next();
+        }
+
+        Some (Token {
+            class: TokenClass::Annotation,
+            value: value.trim().to_string(),
+            line,
+        })
+    }
+
     /// Gets the next token from this character stream.
     pub fn get(&mut self) -> Option<Token> {
+        let token = self.token();
+        let class = token.as_ref().map(|t| t.class);
+
+        // Units may follow numbers and other units
+        self.unit_allowed = matches!(class, Some (TokenClass::Number | TokenClass::Unit));
+
+        // Annotations may follow the variable of a `let` expression
+        self.annotation_allowed = class == Some (TokenClass::Identifier) && self.previous == Some (TokenClass::Let);
+
+        self.previous = class;
+
+        token
+    }
+
+    /// Gets the next token from this character stream, ignoring units.
+    fn token(&mut self) -> Option<Token> {
         use TokenClass::*;
 
+        // Units do not continue onto the next line
+        if self.peek() == Some ('\n') {
+            self.unit_allowed = false;
+        }
+
         self.skip();
 
+        if self.unit_allowed {
+            if let Some (t) = self.unit() {
+                return Some (t);
+            }
+        }
+
+        if self.annotation_allowed {
+            if let Some (t) = self.annotation() {
+                return Some (t);
+            }
+        }
+
+        let line = self.line;
+
         let peek = match self.peek() {
             Some (p) => p,
             None => return None,
@@ -57,9 +189,12 @@ impl Charstream {
         let mut comment = false;
         let mut header = false;
         let mut paragraph = false;
+        let mut text = false;
+
+        let start = self.index;
 
         while let Some (c) = self.peek() {
-            if TOKENBREAK.contains(&c) && !comment && !header && !paragraph {
+            if TOKENBREAK.contains(&c) && !comment && !header && !paragraph && !text {
                 self.next();
                 break;
             }
@@ -68,17 +203,24 @@ impl Charstream {
                 comment = false;
                 header = false;
                 paragraph = false;
+                text = false;
             }
 
-            if comment | header | paragraph {
+            if c == '"' && text {
+                // Closing quotation mark
+                self.next();
+                break;
+            }
+
+            if comment | header | paragraph | text {
                 value.push(c);
             } else if ('A'..='Z').contains(&c) && class == Identifier {
                 value.push(c);
             } else if ('a'..='z').contains(&c) && class == Identifier {
                 value.push(c);
-            } else if c == '_' && class == Identifier {
+            } else if (c == '_' || c == 'µ' || c == 'μ') && class == Identifier {
                 value.push(c);
-            } else if ('0'..'9').contains(&c) && class == Identifier {
+            } else if ('0'..='9').contains(&c) && class == Identifier {
                 value.push(c);
             } else if c == '=' && class == Assignment {
                 value.push(c);
@@ -98,8 +240,18 @@ impl Charstream {
                 value.push(c);
             } else if c == '!' && class == FullSymbolic {
                 value.push(c);
+            } else if c == '?' && class == ShowWork {
+                value.push(c);
             } else if c == ',' && class == Comma {
                 value.push(c);
+            } else if c == ':' && class == Colon {
+                value.push(c);
+            } else if c == '[' && class == OpenBracket {
+                value.push(c);
+            } else if c == ']' && class == CloseBracket {
+                value.push(c);
+            } else if c == '"' && class == Text {
+                text = true;
             } else if c == '@' && class == Header {
                 header = true;
                 value.push(c);
@@ -119,6 +271,14 @@ impl Charstream {
             self.next();
         }
 
+        // Characters that begin no token (e.g. `^` outside a unit) are tokens
+        // of their own, which the parser reports
+        if self.index == start {
+            if let Some (c) = self.next() {
+                value.push(c);
+            }
+        }
+
         // Special cases
 
         // `let` expression
@@ -126,15 +286,74 @@ impl Charstream {
             return Some (Token {
                 class: Let,
                 value: "let".to_string(),
+                line,
+            });
+        }
+
+        // `dimension` declaration
+        if value.as_str() == "dimension" && class == Identifier {
+            let value = self.statement();
+            self.units.declare_dimension(&value);
+
+            return Some (Token {
+                class: Dimension,
+                value,
+                line,
+            });
+        }
+
+        // `unit` declaration (but not a call to `unit`)
+        if value.as_str() == "unit" && class == Identifier && self.peek() != Some ('(') {
+            let value = self.statement();
+
+            // Declared units are recognized in the rest of the input,
+            // unless the declaration fails (which the parser reports)
+            declare(&value, &mut self.units).ok();
+
+            return Some (Token {
+                class: UnitDeclaration,
+                value,
+                line,
+            });
+        }
+
+        // `#pragma` directive
+        if let (Some (p), Comment) = (value.strip_prefix("#pragma"), class) {
+            return Some (Token {
+                class: Pragma,
+                value: p.trim().to_string(),
+                line,
             });
         }
 
         Some (Token {
             class,
             value,
+            line,
         })
     }
 
+    /// Gets the rest of a statement, up to the end of the line or a semicolon.
+    fn statement(&mut self) -> String {
+        let mut value = String::new();
+
+        while let Some (c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+
+            self.next();
+
+            if c == ';' {
+                break;
+            }
+
+            value.push(c);
+        }
+
+        value.trim().to_string()
+    }
+
     /// Peeks at the next character in the character stream.
     pub fn peek(&self) -> Option<char> {
         if self.index < self.chars.len() {
@@ -149,6 +368,10 @@ impl Charstream {
         let c = self.peek();
         self.index += 1;
 
+        if c == Some ('\n') {
+            self.line += 1;
+        }
+
         c
     }
 }
@@ -161,11 +384,12 @@ pub struct Tokenstream {
 }
 
 impl Tokenstream {
-    /// Constructs a new token stream from an input string.
-    pub fn from(input: &str, debug: bool) -> Self {
+    /// Constructs a new token stream from an input string,
+    /// recognizing the provided units.
+    pub fn from(input: &str, debug: bool, units: &UnitTable) -> Self {
         let mut tokens = Vec::new();
 
-        let mut charstream = Charstream::from(input);
+        let mut charstream = Charstream::from(input, units);
 
         while let Some (t) = charstream.get() {
             tokens.push(t);
@@ -202,9 +426,9 @@ impl Tokenstream {
     /// Gets the next token and unwraps it, throwing an
     /// unexpected EOF error if no token is available.
     pub fn next_unwrap(&mut self) -> Token {
-        let last = match self.tokens.iter().last() {
-            Some (t) => t.value.clone(),
-            None => String::from("EOF"),
+        let (last, line) = match self.tokens.iter().last() {
+            Some (t) => (t.value.clone(), t.line),
+            None => (String::from("EOF"), 1),
         };
 
         let next = self.next();
@@ -216,6 +440,7 @@ impl Tokenstream {
                 Token {
                     class: TokenClass::Unknown,
                     value: String::new(),
+                    line,
                 }
             },
         }
@@ -243,7 +468,50 @@ impl Tokenstream {
 
 #[test]
 fn test_tokenization() {
-    let tokens = Tokenstream::from("hello_world = 3", false);
+    let tokens = Tokenstream::from("hello_world = 3", false, &UnitTable::default());
 
     println!("{:#?}", tokens);
-}
\ No newline at end of file
+
+    // Annotations follow the variable of a `let` expression
+    let mut tokens = Tokenstream::from("\nlet sigma: Force / [length]^2 = F / A", false, &UnitTable::default());
+    let classes = std::iter::from_fn(|| tokens.next())
+        .map(|t| (t.class, t.value, t.line))
+        .collect::<Vec<(TokenClass, String, usize)>>();
+
+    assert_eq!(classes[0], (TokenClass::Let, "let".to_string(), 2));
+    assert_eq!(classes[2], (TokenClass::Annotation, "Force / [length]^2".to_string(), 2));
+    assert_eq!(classes[3].0, TokenClass::Assignment);
+}
+
+#[test]
+fn test_unit_expressions() {
+    let mut tokens = Tokenstream::from("9.81 m/s^2 + 1 kg·m²/s² * 2 (kg*m)/s^-2", false, &UnitTable::default());
+    let units = std::iter::from_fn(|| tokens.next())
+        .filter(|t| t.class == TokenClass::Unit)
+        .map(|t| t.value)
+        .collect::<Vec<String>>();
+
+    // Whole unit expressions are single tokens
+    assert_eq!(units, ["m/s^2", "kg·m²/s²", "(kg*m)/s^-2"]);
+}
+
+#[test]
+fn test_unit_fallback() {
+    let tokenize = |input: &str| {
+        let mut tokens = Tokenstream::from(input, false, &UnitTable::default());
+        std::iter::from_fn(|| tokens.next())
+            .map(|t| (t.class, t.value))
+            .collect::<Vec<(TokenClass, String)>>()
+    };
+
+    // A unit followed directly by an operator and an identifier
+    let tokens = tokenize("let x = 2 m^2*y");
+    assert_eq!(tokens[4], (TokenClass::Unit, "m^2".to_string()));
+    assert_eq!(tokens[5].0, TokenClass::Times);
+    assert_eq!(tokens[6], (TokenClass::Identifier, "y".to_string()));
+
+    // Unrecognized characters are consumed
+    let tokens = tokenize("2 ^ 3");
+    assert_eq!(tokens[1], (TokenClass::Unknown, "^".to_string()));
+    assert_eq!(tokens[2], (TokenClass::Number, "3".to_string()));
+}
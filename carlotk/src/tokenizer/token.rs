@@ -7,14 +7,18 @@ use std::fmt;
 pub struct Token {
     pub class: TokenClass,
     pub value: String,
+
+    /// Line of the input on which this token begins
+    pub line: usize,
 }
 
 impl Token {
     /// Constructs a new token.
-    pub fn new(class: TokenClass, value: String) -> Self {
+    pub fn new(class: TokenClass, value: String, line: usize) -> Self {
         Self {
             class,
             value,
+            line,
         }
     }
 
@@ -62,12 +66,18 @@ pub enum TokenClass {
     /// Number
     Number,
 
+    /// Unit following a number
+    Unit,
+
     /// Symbolic
     Symbolic,
 
     /// Full resolve symbolic
     FullSymbolic,
 
+    /// Show work (formula, substitution, and result)
+    ShowWork,
+
     /// Opening parenthesis
     OpenParen,
 
@@ -89,6 +99,30 @@ pub enum TokenClass {
     /// Comma
     Comma,
 
+    /// Colon
+    Colon,
+
+    /// Opening bracket
+    OpenBracket,
+
+    /// Closing bracket
+    CloseBracket,
+
+    /// Text (string literal)
+    Text,
+
+    /// Pragma
+    Pragma,
+
+    /// Unit declaration
+    UnitDeclaration,
+
+    /// Dimension declaration
+    Dimension,
+
+    /// Dimension annotation on a variable
+    Annotation,
+
     /// Unknown
     Unknown,
 }
@@ -102,6 +136,7 @@ impl From<TokenClass> for u8 {
             Identifier      => 0,
             Assignment      => 2,
             Number          => 0,
+            Unit            => 0,
             Unknown         => 0,
             Plus            => 3,
             Minus           => 3,
@@ -109,6 +144,7 @@ impl From<TokenClass> for u8 {
             Divide          => 4,
             Symbolic        => 1,
             FullSymbolic    => 1,
+            ShowWork        => 1,
             OpenParen       => 2,
             CloseParen      => 2,
             Newline         => 0,
@@ -116,6 +152,14 @@ impl From<TokenClass> for u8 {
             Paragraph       => 0,
             Comment         => 0,
             Comma           => 0,
+            Colon           => 0,
+            OpenBracket     => 0,
+            CloseBracket    => 0,
+            Text            => 0,
+            Pragma          => 0,
+            UnitDeclaration => 0,
+            Dimension       => 0,
+            Annotation      => 0,
         }
     }
 }
@@ -127,7 +171,7 @@ impl From<char> for TokenClass {
         use TokenClass::*;
 
         match c {
-            'a'..='z' | 'A'..='Z' | '_' => Identifier,
+            'a'..='z' | 'A'..='Z' | '_' | 'µ' | 'μ' => Identifier,
             '=' => Assignment,
             '+' => Plus,
             '-' => Minus,
@@ -135,6 +179,7 @@ impl From<char> for TokenClass {
             '/' => Divide,
             '&' => Symbolic,
             '!' => FullSymbolic,
+            '?' => ShowWork,
             '(' => OpenParen,
             ')' => CloseParen,
             '0'..='9' => Number,
@@ -143,6 +188,10 @@ impl From<char> for TokenClass {
             '@' => Header,
             '~' => Paragraph,
             ',' => Comma,
+            ':' => Colon,
+            '[' => OpenBracket,
+            ']' => CloseBracket,
+            '"' => Text,
             _ => Unknown,
         }
     }
@@ -157,6 +206,7 @@ impl fmt::Display for TokenClass {
             Identifier      => "Identifier",
             Assignment      => "Assignment",
             Number          => "Number",
+            Unit            => "Unit",
             Unknown         => "Unknown",
             Plus            => "Plus",
             Minus           => "Minus",
@@ -164,6 +214,7 @@ impl fmt::Display for TokenClass {
             Divide          => "Divide",
             Symbolic        => "Symbolic",
             FullSymbolic    => "FullSymbolic",
+            ShowWork        => "ShowWork",
             Newline         => "Newline",
             OpenParen       => "OpenParen",
             CloseParen      => "CloseParen",
@@ -171,6 +222,14 @@ impl fmt::Display for TokenClass {
             Header          => "Header",
             Paragraph       => "Paragraph",
             Comma           => "Comma",
+            Colon           => "Colon",
+            OpenBracket     => "OpenBracket",
+            CloseBracket    => "CloseBracket",
+            Text            => "Text",
+            Pragma          => "Pragma",
+            UnitDeclaration => "UnitDeclaration",
+            Dimension       => "Dimension",
+            Annotation      => "Annotation",
         };
 
         write!(f, "{}", string)
@@ -0,0 +1,370 @@
+//! Renders expressions as Typst math and markup.
+
+use crate::{
+    BinaryOperation,
+    carlo_std,
+    Dimensions,
+    DisplaySettings,
+    Expression,
+    Number,
+    expression::{
+        explicit_product,
+        needs_parentheses,
+        ratio,
+        table_rows,
+        unit_parts,
+        work_rows,
+    },
+    identifier::{
+        ACCENTS,
+        Identifier,
+        Part,
+    },
+};
+
+/// Characters with a meaning in Typst markup
+const MARKUP: [char; 12] = ['\\', '#', '$', '*', '_', '@', '<', '>', '[', ']', '`', '='];
+
+/// Writes text as a Typst string (e.g. `"kg"`), which math displays upright.
+pub(crate) fn typst_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Escapes the characters of text that have a meaning in Typst markup,
+/// including comments (`//` and `/*`) and leading list markers (e.g. `- ` or `1. `).
+pub fn escape_typst(text: &str) -> String {
+    let mut output = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some (c) = chars.next() {
+        if MARKUP.contains(&c) || (c == '/' && matches!(chars.peek(), Some ('/' | '*'))) {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+
+    let digits = output.chars().take_while(|c| c.is_ascii_digit()).count();
+
+    if output.starts_with(['-', '+']) {
+        output.insert(0, '\\');
+    } else if digits > 0 && output[digits..].starts_with('.') {
+        output.insert(digits, '\\');
+    }
+
+    output
+}
+
+impl Expression {
+    /// Converts this expression to Typst.
+    pub fn typst(&self, toplevel: bool, settings: &DisplaySettings) -> String {
+        use Expression::*;
+
+        match self {
+            Assignment {
+                left,
+                right,
+                ..
+            } => format!("{} := {}", typst_identifier(left, settings), right.typst(true, settings)),
+            Reassignment {
+                left,
+                right,
+                ..
+            } => format!("{} = {}", typst_identifier(left, settings), right.typst(true, settings)),
+            Float {
+                value,
+                kg,
+                m,
+                s,
+                a,
+                k,
+                mol,
+                cd,
+                other,
+            } => typst_unit(value.to_owned(), (*kg, *m, *s, *a, *k, *mol, *cd), other, settings),
+            Identifier (s) => typst_identifier(s, settings),
+            Symbolic (s) | FullSymbolic (s) | ShowWork (s) => typst_identifier(s, settings),
+            Substituted (e) => typst_substituted(e, toplevel, settings),
+            Work {
+                left,
+                formula,
+                substituted,
+                value,
+            } => {
+                let rows = work_rows(formula, [
+                    formula.typst(true, settings),
+                    typst_substituted(substituted, true, settings),
+                    value.typst(true, settings),
+                ]);
+
+                format!("{} &= {}", typst_identifier(left, settings), rows.join(" \\\n&= "))
+            },
+            BinOp {
+                left,
+                oper: BinaryOperation::Div,
+                right,
+            } => format!("frac({}, {})", left.typst(true, settings), right.typst(true, settings)),
+            BinOp {
+                left,
+                oper,
+                right,
+            } => {
+                let output = typst_operation(left, *oper, right, &|e, toplevel| e.typst(toplevel, settings));
+
+                if toplevel {
+                    output
+                } else {
+                    format!("({})", output)
+                }
+            },
+            FnCall {
+                name,
+                arguments,
+            } => carlo_std::typst(name, arguments, settings),
+            NamedArgument {
+                name,
+                value,
+            } => format!("{} = {}", typst_string(name), value.typst(true, settings)),
+            Text (s) => typst_string(&format!("“{}”", s)),
+            Vector (v) => format!(
+                "[{}]",
+                v.iter()
+                    .map(|e| e.typst(true, settings))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ),
+            Table (t) => typst_table(t, settings),
+            Formatted {
+                value,
+                notation,
+            } => value.typst(toplevel, &settings.with_notation(*notation)),
+            Ratio {
+                value,
+                unit,
+            } => match ratio(value, unit, settings) {
+                Some (n) => format!("{} thick {}", settings.typst_number(&n), typst_string(unit)),
+                None => value.typst(toplevel, settings),
+            },
+            Paragraph (s) => escape_typst(s.trim()),
+            Pragma { .. } | Null => String::new(),
+            UnitDeclaration {
+                name,
+                value,
+            } => match value {
+                Some (v) => format!("1 thick {} = {}", typst_string(name), v.typst(true, settings)),
+                None => typst_string(name),
+            },
+            Dimension (s) => typst_string(s),
+            Header (s) => format!("= {}", escape_typst(s)),
+            Subheader (s) => format!("== {}", escape_typst(s)),
+            Subsubheader (s) => format!("=== {}", escape_typst(s)),
+        }
+    }
+}
+
+/// Converts a value and its units to Typst (e.g. `9.81 thick "m" thick "s"^(-2)`).
+fn typst_unit(
+    value: Number,
+    dimensions: (f64, f64, f64, f64, f64, f64, f64),
+    other: &Dimensions,
+    settings: &DisplaySettings,
+) -> String {
+    let (value, parts) = unit_parts(value, dimensions, other, settings);
+    let mut output = settings.typst_number(&value);
+
+    for (prefix, unit, pow) in parts {
+        let prefix = match prefix {
+            Some ("u") => "µ",
+            Some (p) => p,
+            None => "",
+        };
+        let unit = typst_string(&format!("{}{}", prefix, unit));
+
+        if pow == 1.0 {
+            output.push_str(&format!(" thick {}", unit));
+        } else {
+            output.push_str(&format!(" thick {}^({})", unit, pow));
+        }
+    }
+
+    output
+}
+
+/// Converts a table to a Typst matrix without delimiters,
+/// with its column names as the first row.
+fn typst_table(columns: &[(String, Expression)], settings: &DisplaySettings) -> String {
+    let mut rows = vec![
+        columns.iter()
+            .map(|(name, _)| typst_identifier(name, settings))
+            .collect::<Vec<String>>(),
+    ];
+    rows.extend(table_rows(columns, &|e| e.typst(true, settings)));
+
+    format!(
+        "mat(delim: #none, {})",
+        rows.iter()
+            .map(|row| row.join(", "))
+            .collect::<Vec<String>>()
+            .join("; "),
+    )
+}
+
+/// Converts an expression with values substituted for its variables to Typst,
+/// parenthesizing values with units (e.g. `(10 kg) (9.81 m s^-2)`).
+fn typst_substituted(expr: &Expression, toplevel: bool, settings: &DisplaySettings) -> String {
+    use Expression::*;
+
+    match expr {
+        // Fractions separate their operands without parentheses
+        BinOp {
+            left,
+            oper: BinaryOperation::Div,
+            right,
+        } => format!(
+            "frac({}, {})",
+            typst_substituted(left, true, settings),
+            typst_substituted(right, true, settings),
+        ),
+        BinOp {
+            left,
+            oper,
+            right,
+        } => {
+            let output = typst_operation(left, *oper, right, &|e, toplevel| match e {
+                Float { .. } if e.is_dimensionless() => e.typst(true, settings),
+                Float { .. } | Formatted { .. } | Ratio { .. } => format!("({})", e.typst(true, settings)),
+                e => typst_substituted(e, toplevel, settings),
+            });
+
+            if toplevel {
+                output
+            } else {
+                format!("({})", output)
+            }
+        },
+        e => e.typst(toplevel, settings),
+    }
+}
+
+/// Converts an addition, subtraction, or multiplication to Typst, rendering each
+/// operand with the provided function (which is told whether the operand may be
+/// written without parentheses).
+///
+/// Operands are parenthesized as in LaTeX, and products are written with `dot`
+/// where juxtaposition would be ambiguous (e.g. `Delta_(x) dot 4`).
+fn typst_operation(
+    left: &Expression,
+    oper: BinaryOperation,
+    right: &Expression,
+    render: &dyn Fn(&Expression, bool) -> String,
+) -> String {
+    let operand = |e: &Expression, is_right: bool| {
+        let parenthesize = needs_parentheses(e, oper, is_right);
+
+        match e {
+            Expression::BinOp { .. } => render(e, !parenthesize),
+            _ => {
+                let typst = render(e, true);
+
+                // Values may already be parenthesized (e.g. values with units in substitutions)
+                if parenthesize && !typst.starts_with('(') {
+                    format!("({})", typst)
+                } else {
+                    typst
+                }
+            },
+        }
+    };
+
+    let l = operand(left, false);
+    let r = operand(right, true);
+
+    let symbol = match oper {
+        BinaryOperation::Mul if explicit_product(left, &l, &r, ")") => " dot ".to_string(),
+        _ => oper.typst(),
+    };
+
+    format!("{}{}{}", l, symbol, r)
+}
+
+/// Converts an identifier to Typst, writing Greek letters by name, subscripts
+/// separated by commas (e.g. `sigma_("max")`), and accents and primes around
+/// and after the base (e.g. `dot(x)'` for `x_dot_prime`), or as the LaTeX
+/// given for it in its assignment.
+///
+/// Words of more than one letter are quoted, since Typst would read them as symbols.
+fn typst_identifier(id: &str, settings: &DisplaySettings) -> String {
+    let id = Identifier::new(id, settings);
+
+    let mut base = typst_part(&id.base, true);
+    for accent in &id.accents {
+        if let Some ((.., function)) = ACCENTS.iter().find(|(name, ..)| name == accent) {
+            base = format!("{}({})", function, base);
+        }
+    }
+
+    let primes = "'".repeat(id.primes);
+
+    if id.subscript.is_empty() {
+        format!("{}{}", base, primes)
+    } else {
+        let subscript = id.subscript.iter()
+            .map(|p| typst_part(p, false))
+            .collect::<Vec<String>>();
+
+        format!("{}{}_({})", base, primes, subscript.join(", "))
+    }
+}
+
+/// Converts a part of an identifier to Typst, writing words in italics in the base.
+fn typst_part(part: &Part, base: bool) -> String {
+    match part {
+        Part::Greek (.., name) => name.to_owned(),
+        Part::Word (w) if base => format!("italic({})", typst_string(w)),
+        Part::Word (w) => typst_string(w),
+        Part::Letter (s) | Part::Number (s) => s.to_owned(),
+    }
+}
+
+#[test]
+fn test_typst() {
+    let settings = DisplaySettings::default();
+    let parser = crate::Parser::new(false);
+
+    assert_eq!(typst_identifier("sigma_max", &settings), "sigma_(\"max\")");
+    assert_eq!(typst_identifier("F_y", &settings), "F_(y)");
+    assert_eq!(typst_identifier("Fy", &settings), "italic(\"Fy\")");
+    assert_eq!(escape_typst("5 * 2 = #10"), "5 \\* 2 \\= \\#10");
+    assert_eq!(escape_typst("- not a list"), "\\- not a list");
+    assert_eq!(escape_typst("+ 2 kN"), "\\+ 2 kN");
+    assert_eq!(escape_typst("1. not a list"), "1\\. not a list");
+    assert_eq!(escape_typst("see https://example.com /* note */"), "see https:\\//example.com \\/\\* note \\*/");
+    assert_eq!(escape_typst("a/b - c"), "a/b - c");
+
+    let expressions = parser.parse("let a = 10 kg\nlet b = sqrt(a / (2 m))\n");
+    let values = expressions.iter()
+        .filter(|e| !matches!(e, Expression::Null))
+        .map(|e| e.typst(true, &settings))
+        .collect::<Vec<String>>();
+
+    assert_eq!(values, [
+        "a := 10 thick \"kg\"",
+        "b := sqrt(frac(a, 2 thick \"m\"))",
+    ]);
+
+    // Accents decorate the base, and ambiguous products are explicit
+    assert_eq!(typst_identifier("x_dot", &settings), "dot(x)");
+    assert_eq!(typst_identifier("PI", &settings), "italic(\"PI\")");
+
+    let values = parser.parse("let y = sqrt(Delta_x * 4)\nlet z = 2 m * x\n").iter()
+        .map(|e| e.typst(true, &settings))
+        .collect::<Vec<String>>();
+    assert_eq!(values, ["y := sqrt(Delta_(x) dot 4)", "z := 2 thick \"m\" dot x"]);
+
+    let mut env = crate::Environment::new();
+    let typst = env.typst_evaluate(&parser.parse("@ Loads\n~ Mass *per* length.\nlet w = 2 kg / 1 m\n"));
+    assert_eq!(typst, "= Loads\n\nMass \\*per\\* length.\n\n$ w := 2.0000 thick \"kg\" thick \"m\"^(-1) $\n\n");
+
+    // Accents, primes, and the LaTeX given for identifiers
+    let typst = env.typst_evaluate(&parser.parse("let x_dot = 2\nlet sigma_max_prime = 3\nlet tau_c \"\\tau_{\\mathrm{c}}\" = 4\n"));
+    assert_eq!(typst, "$ dot(x) := 2.0000 $\n\n$ sigma'_(\"max\") := 3.0000 $\n\n$ tau_(\"c\") := 4.0000 $\n\n");
+}
@@ -1,33 +1,305 @@
 //! Defines units and prefixes.
 
-/// Defines the units available to the Carlo language.
-/// Each unit is structured as (name, (multiplier, kg, m, s, A, K, mol))
-pub const UNITS: [(&str, (f64, f64, f64, f64, f64, f64, f64)); 16] = [
-    ("g", (0.001, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
-    ("m", (1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0)),
-    ("s", (1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0)),
-    ("N", (1.0, 1.0, 1.0, -2.0, 0.0, 0.0, 0.0)),
-    ("Pa", (1.0, 1.0, -1.0, -2.0, 0.0, 0.0, 0.0)),
-    ("L", (0.001, 0.0, 3.0, 0.0, 0.0, 0.0, 0.0)),
-    ("Hz", (1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0)),
-    ("J", (1.0, 1.0, 2.0, -2.0, 0.0, 0.0, 0.0)),
-    ("W", (1.0, 1.0, 2.0, -3.0, 0.0, 0.0, 0.0)),
-    ("A", (1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0)),
-    ("C", (1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0)),
-    ("V", (1.0, 1.0, 2.0, -3.0, -1.0, 0.0, 0.0)),
-    ("O", (1.0, 1.0, 2.0, -3.0, -2.0, 0.0, 0.0)),
-    ("F", (1.0, -1.0, -2.0, 4.0, 2.0, 0.0, 0.0)),
-    ("K", (1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0)),
-    ("mol", (1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0)),
+use crate::{
+    Dimensions,
+    Expression,
+    Number,
+};
+
+/// A unit, structured as (name, (multiplier, kg, m, s, A, K, mol, cd)).
+pub type UnitDefinition = (&'static str, (f64, f64, f64, f64, f64, f64, f64, f64));
+
+/// Defines the SI units available to the Carlo language.
+/// The ohm may be written `Ω` (or the ohm sign `Ω`), or `O` for compatibility.
+pub const UNITS: [UnitDefinition; 29] = [
+    ("g", (0.001, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("m", (1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("s", (1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0)),
+    ("N", (1.0, 1.0, 1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Pa", (1.0, 1.0, -1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("L", (0.001, 0.0, 3.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Hz", (1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0)),
+    ("J", (1.0, 1.0, 2.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("W", (1.0, 1.0, 2.0, -3.0, 0.0, 0.0, 0.0, 0.0)),
+    ("A", (1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0)),
+    ("C", (1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0)),
+    ("V", (1.0, 1.0, 2.0, -3.0, -1.0, 0.0, 0.0, 0.0)),
+    ("Ω", (1.0, 1.0, 2.0, -3.0, -2.0, 0.0, 0.0, 0.0)), // Greek capital omega
+    ("Ω", (1.0, 1.0, 2.0, -3.0, -2.0, 0.0, 0.0, 0.0)), // ohm sign
+    ("O", (1.0, 1.0, 2.0, -3.0, -2.0, 0.0, 0.0, 0.0)),
+    ("S", (1.0, -1.0, -2.0, 3.0, 2.0, 0.0, 0.0, 0.0)),
+    ("F", (1.0, -1.0, -2.0, 4.0, 2.0, 0.0, 0.0, 0.0)),
+    ("Wb", (1.0, 1.0, 2.0, -2.0, -1.0, 0.0, 0.0, 0.0)),
+    ("T", (1.0, 1.0, 0.0, -2.0, -1.0, 0.0, 0.0, 0.0)),
+    ("H", (1.0, 1.0, 2.0, -2.0, -2.0, 0.0, 0.0, 0.0)),
+    ("K", (1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0)),
+    ("mol", (1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0)),
+    ("kat", (1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 1.0, 0.0)),
+    ("cd", (1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0)),
+    ("lm", (1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0)),
+    ("lx", (1.0, 0.0, -2.0, 0.0, 0.0, 0.0, 0.0, 1.0)),
+    ("Bq", (1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Gy", (1.0, 0.0, 2.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("Sv", (1.0, 0.0, 2.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+];
+
+/// Defines the US customary units available to the Carlo language,
+/// which never take a prefix.
+pub const CUSTOMARY: [UnitDefinition; 7] = [
+    ("in", (0.0254, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("ft", (0.3048, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("lb", (0.45359237, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("lbf", (4.4482216152605, 1.0, 1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("kip", (4448.2216152605, 1.0, 1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("psi", (6894.757293168361, 1.0, -1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+    ("ksi", (6894757.293168361, 1.0, -1.0, -2.0, 0.0, 0.0, 0.0, 0.0)),
+];
+
+/// Defines the dimensionless units available to the Carlo language,
+/// which never take a prefix.  Values in these units keep their unit for display
+/// (e.g. `5 %`), and decibels are converted as power ratios (so `10 dB` is 10).
+pub const DIMENSIONLESS: [UnitDefinition; 6] = [
+    ("percent", (0.01, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("%", (0.01, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("permille", (0.001, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("‰", (0.001, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("ppm", (1E-6, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+    ("dB", (1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+];
+
+/// Defines the built-in `information` dimension, which is not an SI base dimension.
+pub const INFORMATION: &str = "information";
+
+/// Defines the units of information available to the Carlo language,
+/// structured as (name, bits).  The bit is the base unit of information.
+/// These units also take binary prefixes (e.g. `MiB`).
+pub const INFORMATION_UNITS: [(&str, f64); 2] = [
+    ("bit", 1.0),
+    ("B", 8.0),
+];
+
+/// Defines the units whose names already carry a prefix, structured as
+/// (name, unprefixed unit, multiplier).  Prefixes are chosen for these units
+/// from the unprefixed unit on output (e.g. `mg` or `Mg` rather than `kg`).
+pub const PREFIXED: [(&str, &str, f64); 1] = [
+    ("kg", "g", 1E3),
 ];
 
 /// Defines the prefixes available to the Carlo language.
-pub const PREFIXES: [(char, f64); 7] = [
-    ('n', 1E-9),
-    ('u', 1E-6),
-    ('m', 1E-3),
-    ('c', 1E-2),
-    ('k', 1E+3),
-    ('M', 1E+6),
-    ('G', 1E+9),
-];
\ No newline at end of file
+pub const PREFIXES: [(&str, f64); 18] = [
+    ("a", 1E-18),
+    ("f", 1E-15),
+    ("p", 1E-12),
+    ("n", 1E-9),
+    ("u", 1E-6),
+    ("µ", 1E-6), // micro sign
+    ("μ", 1E-6), // Greek mu
+    ("m", 1E-3),
+    ("c", 1E-2),
+    ("d", 1E-1),
+    ("da", 1E+1),
+    ("h", 1E+2),
+    ("k", 1E+3),
+    ("M", 1E+6),
+    ("G", 1E+9),
+    ("T", 1E+12),
+    ("P", 1E+15),
+    ("E", 1E+18),
+];
+
+/// Defines the binary prefixes available to units of information,
+/// which are chosen on output for bytes under `#pragma bytes`.
+pub const BINARY_PREFIXES: [(&str, f64); 4] = [
+    ("Ki", 1024.0),
+    ("Mi", 1048576.0),
+    ("Gi", 1073741824.0),
+    ("Ti", 1099511627776.0),
+];
+
+#[derive(Clone, Debug)]
+/// Table of the units available to a Carlo program,
+/// including units and dimensions declared in the program.
+pub struct UnitTable {
+    /// Units by name, each with its value in base units
+    units: Vec<(String, Expression)>,
+
+    /// Declared base dimensions, each with its base unit (if declared)
+    dimensions: Vec<(String, Option<String>)>,
+}
+
+impl Default for UnitTable {
+    fn default() -> Self {
+        let mut units = UNITS.iter()
+            .chain(CUSTOMARY.iter())
+            .chain(DIMENSIONLESS.iter())
+            .map(|(name, (mult, kg, m, s, a, k, mol, cd))| (name.to_string(), Expression::Float {
+                value: Number::exact(*mult),
+                kg: *kg,
+                m: *m,
+                s: *s,
+                a: *a,
+                k: *k,
+                mol: *mol,
+                cd: *cd,
+                other: Dimensions::new(),
+            }))
+            .collect::<Vec<(String, Expression)>>();
+
+        for (name, bits) in INFORMATION_UNITS {
+            units.push((name.to_string(), Expression::Float {
+                value: Number::exact(bits),
+                kg: 0.0,
+                m: 0.0,
+                s: 0.0,
+                a: 0.0,
+                k: 0.0,
+                mol: 0.0,
+                cd: 0.0,
+                other: Dimensions::base(INFORMATION),
+            }));
+        }
+
+        Self {
+            units,
+            dimensions: vec![(INFORMATION.to_string(), Some (INFORMATION_UNITS[0].0.to_string()))],
+        }
+    }
+}
+
+impl UnitTable {
+    /// Gets the value of a unit in base units.
+    pub fn get(&self, name: &str) -> Option<&Expression> {
+        self.units.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, u)| u)
+    }
+
+    /// Checks if a unit is defined.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Checks if a unit is defined and can take a prefix.
+    pub fn prefixable(&self, name: &str) -> bool {
+        self.contains(name) && !CUSTOMARY.iter().chain(DIMENSIONLESS.iter()).any(|(n, _)| *n == name)
+    }
+
+    /// Checks if a unit is defined and can take a binary prefix.
+    pub fn binary_prefixable(&self, name: &str) -> bool {
+        INFORMATION_UNITS.iter().any(|(n, _)| *n == name) && self.contains(name)
+    }
+
+    /// Defines a unit, replacing any unit of the same name.
+    pub fn define(&mut self, name: &str, value: Expression) {
+        self.units.retain(|(n, _)| n != name);
+        self.units.push((name.to_string(), value));
+    }
+
+    /// Declares a base dimension, returning `false` if it already exists.
+    pub fn declare_dimension(&mut self, name: &str) -> bool {
+        if self.has_dimension(name) {
+            return false;
+        }
+
+        self.dimensions.push((name.to_string(), None));
+        true
+    }
+
+    /// Checks if a base dimension has been declared.
+    pub fn has_dimension(&self, name: &str) -> bool {
+        self.dimensions.iter().any(|(d, _)| d == name)
+    }
+
+    /// Defines a unit as the base unit of the most recently declared dimension,
+    /// returning its value (or `None` if that dimension already has a base unit).
+    pub fn define_base(&mut self, name: &str) -> Option<Expression> {
+        let (dimension, base) = self.dimensions.last_mut()?;
+
+        if base.is_some() {
+            return None;
+        }
+
+        *base = Some (name.to_string());
+
+        let value = Expression::Float {
+            value: Number::exact(1.0),
+            kg: 0.0,
+            m: 0.0,
+            s: 0.0,
+            a: 0.0,
+            k: 0.0,
+            mol: 0.0,
+            cd: 0.0,
+            other: Dimensions::base(dimension),
+        };
+
+        self.define(name, value.to_owned());
+        Some (value)
+    }
+
+    /// Gets the name of the base unit of a declared dimension
+    /// (or the name of the dimension if it has no base unit).
+    pub fn base_unit(&self, dimension: &str) -> String {
+        match self.dimensions.iter().find(|(d, _)| d == dimension) {
+            Some ((_, Some (u))) => u.to_owned(),
+            _ => format!("[{}]", dimension),
+        }
+    }
+}
+
+#[test]
+fn test_unit_table() {
+    let mut units = UnitTable::default();
+
+    // Base units of declared dimensions
+    assert!(units.define_base("USD").is_none());
+    assert!(units.declare_dimension("currency"));
+    assert!(!units.declare_dimension("currency"));
+    assert_eq!(units.base_unit("currency"), "[currency]");
+
+    match units.define_base("USD") {
+        Some (Expression::Float { value, other, .. }) => {
+            assert_eq!(value.to_f64(), 1.0);
+            assert_eq!(other, Dimensions::base("currency"));
+        },
+        _ => panic!("expected a base unit"),
+    }
+
+    assert!(units.define_base("EUR").is_none());
+    assert_eq!(units.base_unit("currency"), "USD");
+
+    // Declared units can take prefixes and replace existing units
+    assert!(units.prefixable("USD"));
+    units.define("m", Expression::dimensionless(Number::exact(2.0)));
+    assert!(units.get("m").is_some_and(|m| m.is_dimensionless()));
+}
+
+#[test]
+fn test_information() {
+    let units = UnitTable::default();
+
+    // Only units of information take binary prefixes
+    assert!(units.binary_prefixable("B"));
+    assert!(units.binary_prefixable("bit"));
+    assert!(!units.binary_prefixable("m"));
+    assert!(units.has_dimension(INFORMATION));
+    assert_eq!(units.base_unit(INFORMATION), "bit");
+
+    // Decimal prefixes are chosen for bits, and binary prefixes for bytes
+    let parser = crate::Parser::new(false);
+    let mut env = crate::Environment::new();
+    let output = env.evaluate(&parser.parse("#pragma prefixes\nlet a = 3000 bit\nlet b = 5 Mbit/s\n#pragma bytes\nlet c = 2048 KiB\nlet d = 0.5 B\n"));
+
+    assert_eq!(output, "a = 3.0000 kbit\nb = 5.0000 Mbit s^-1\nc = 2.0000 MiB\nd = 0.5000 B\n");
+}
+
+#[test]
+fn test_derived_units() {
+    let parser = crate::Parser::new(false);
+    let mut env = crate::Environment::new();
+
+    // Derived units are chosen on output where they match exactly
+    let output = env.evaluate(&parser.parse("let a = 10 V / 2 A\nlet b = 2 Wb / 1 m^2\nlet c = 2 Wb / 1 A\nlet d = 3 lm / 1 m^2\nlet e = 5 cd\nlet f = 2 kat\nlet g = 4 S\nlet h = 3 Bq\nlet i = 2 kO\n"));
+
+    assert_eq!(output, "a = 5.0000 Ω\nb = 2.0000 T\nc = 2.0000 H\nd = 3.0000 lx\ne = 5.0000 cd\nf = 2.0000 kat\ng = 4.0000 S\nh = 3.0000 Hz\ni = 2000.0000 Ω\n");
+}
@@ -0,0 +1,181 @@
+//! Defines the unit systems used to display values.
+
+use crate::{
+    Expression,
+    Number,
+    UnitTable,
+    parser::unit_value,
+};
+
+/// A unit in a unit system, as a product of units raised to powers
+/// (e.g. `[("N", 1.0), ("mm", 1.0)]` for newton-millimeters).
+pub type SystemUnit = &'static [(&'static str, f64)];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// A coherent set of units used to display values by dimension
+/// (e.g. stress in MPa and length in mm).
+pub struct UnitSystem {
+    /// Name of this unit system
+    pub name: &'static str,
+
+    /// Units for mass, length, time, current, temperature, amount of substance,
+    /// and luminous intensity,
+    /// used when no preferred unit has the dimensions of a value
+    pub base: [&'static str; 7],
+
+    /// Units in order of preference
+    pub preferred: &'static [SystemUnit],
+}
+
+/// Defines the unit systems available to the Carlo language.
+pub const UNIT_SYSTEMS: [UnitSystem; 3] = [
+    UnitSystem {
+        name: "SI",
+        base: ["kg", "m", "s", "A", "K", "mol", "cd"],
+        preferred: &[
+            &[("kg", 1.0)],
+            &[("m", 1.0)],
+            &[("s", 1.0)],
+            &[("N", 1.0)],
+            &[("Pa", 1.0)],
+            &[("J", 1.0)],
+            &[("W", 1.0)],
+            &[("Hz", 1.0)],
+            &[("A", 1.0)],
+            &[("C", 1.0)],
+            &[("V", 1.0)],
+            &[("Ω", 1.0)],
+            &[("S", 1.0)],
+            &[("F", 1.0)],
+            &[("Wb", 1.0)],
+            &[("T", 1.0)],
+            &[("H", 1.0)],
+            &[("K", 1.0)],
+            &[("mol", 1.0)],
+            &[("kat", 1.0)],
+            &[("cd", 1.0)],
+            &[("lx", 1.0)],
+        ],
+    },
+    UnitSystem {
+        name: "mm-N-MPa",
+        base: ["kg", "mm", "s", "A", "K", "mol", "cd"],
+        preferred: &[
+            &[("mm", 1.0)],
+            &[("N", 1.0)],
+            &[("MPa", 1.0)],
+            &[("N", 1.0), ("mm", 1.0)],
+            &[("N", 1.0), ("mm", -1.0)],
+            &[("mm", 2.0)],
+            &[("mm", 3.0)],
+            &[("mm", 4.0)],
+            &[("kg", 1.0)],
+            &[("s", 1.0)],
+        ],
+    },
+    UnitSystem {
+        name: "US",
+        base: ["lb", "in", "s", "A", "K", "mol", "cd"],
+        preferred: &[
+            &[("in", 1.0)],
+            &[("lbf", 1.0)],
+            &[("psi", 1.0)],
+            &[("lbf", 1.0), ("in", 1.0)],
+            &[("lbf", 1.0), ("in", -1.0)],
+            &[("in", 2.0)],
+            &[("in", 3.0)],
+            &[("in", 4.0)],
+            &[("lb", 1.0)],
+            &[("s", 1.0)],
+        ],
+    },
+];
+
+impl Default for UnitSystem {
+    fn default() -> Self {
+        UNIT_SYSTEMS[0]
+    }
+}
+
+impl UnitSystem {
+    /// Gets a unit system by name, ignoring case (e.g. `si`, `mm-N-MPa`, or `us`).
+    pub fn named(name: &str) -> Option<Self> {
+        UNIT_SYSTEMS.iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Chooses the units for a value, returning the value of those units
+    /// and each unit with its power.
+    ///
+    /// The first preferred unit with the dimensions of the value is used,
+    /// otherwise the value is expressed in the base units of this system.
+    /// Additional base dimensions are left to the caller.
+    pub fn units(&self, value: &Expression, table: &UnitTable) -> Option<(Number, Vec<(&'static str, f64)>)> {
+        let (kg, m, s, a, k, mol, cd) = match value {
+            Expression::Float { kg, m, s, a, k, mol, cd, .. } => (*kg, *m, *s, *a, *k, *mol, *cd),
+            _ => return None,
+        };
+
+        for unit in self.preferred {
+            if let Some (Expression::Float { value: v, .. }) = evaluate(unit, table).filter(|u| u.same_dimensions(value)) {
+                return Some ((v, unit.to_vec()));
+            }
+        }
+
+        let parts = self.base.iter()
+            .zip([kg, m, s, a, k, mol, cd])
+            .filter(|(_, pow)| *pow != 0.0)
+            .map(|(unit, pow)| (*unit, pow))
+            .collect::<Vec<(&str, f64)>>();
+
+        match evaluate(&parts, table) {
+            Some (Expression::Float { value: v, .. }) => Some ((v, parts)),
+            _ => Some ((Number::exact(1.0), parts)),
+        }
+    }
+}
+
+/// Evaluates a product of units raised to powers.
+fn evaluate(unit: &[(&str, f64)], table: &UnitTable) -> Option<Expression> {
+    if unit.is_empty() {
+        return Some (Expression::dimensionless(Number::exact(1.0)));
+    }
+
+    let input = unit.iter()
+        .map(|(u, pow)| format!("{}^({})", u, pow))
+        .collect::<Vec<String>>()
+        .join("*");
+
+    unit_value(&input, table)
+}
+
+#[test]
+fn test_unit_systems() {
+    let table = UnitTable::default();
+    let value = |input: &str| unit_value(input, &table).unwrap();
+    let close = |a: Number, b: f64| (a.to_f64() - b).abs() <= 1E-9 * b.abs();
+
+    // Preferred units by dimension
+    let mm = UnitSystem::named("mm-n-mpa").unwrap();
+    let (v, parts) = mm.units(&value("MPa"), &table).unwrap();
+    assert!(close(v, 1E6));
+    assert_eq!(parts, vec![("MPa", 1.0)]);
+
+    let (v, parts) = mm.units(&value("kN*m"), &table).unwrap();
+    assert!(close(v, 1E-3));
+    assert_eq!(parts, vec![("N", 1.0), ("mm", 1.0)]);
+
+    // Base units of the system when no preferred unit matches
+    let (v, parts) = mm.units(&value("m/s"), &table).unwrap();
+    assert!(close(v, 1E-3));
+    assert_eq!(parts, vec![("mm", 1.0), ("s", -1.0)]);
+
+    let us = UnitSystem::named("US").unwrap();
+    let (v, parts) = us.units(&value("Pa"), &table).unwrap();
+    assert!(close(v, 6894.757293168361));
+    assert_eq!(parts, vec![("psi", 1.0)]);
+
+    assert_eq!(UnitSystem::default().units(&value("g"), &table).unwrap().1, vec![("kg", 1.0)]);
+    assert!(UnitSystem::named("imperial").is_none());
+}
@@ -0,0 +1,15 @@
+@ Pull Test
+
+~ Measured data is imported from a CSV file next to this one.
+
+let data = csv("pull.csv")
+
+let raw = csv("pull.csv", units: [s, kN])
+
+data
+
+column(data, "force")
+
+let F = column(data, "force") * 2
+
+F
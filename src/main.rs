@@ -12,9 +12,13 @@ fn main() {
         using args
 
         subcommand run
+        subcommand check
         subcommand repl
         subcommand help
         subcommand latex
+        subcommand markdown
+        subcommand html
+        subcommand typst
         subcommand version
     };
 }
\ No newline at end of file
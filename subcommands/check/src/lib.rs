@@ -0,0 +1,49 @@
+//! Defines the CHECK subcommand.
+
+use std::process::exit;
+
+use carlotk::prelude::*;
+
+const HELP: &str = include_str!("../help_check.txt");
+
+/// Provide help to the user
+pub fn helpme() {
+    printhelp(HELP);
+}
+
+pub fn check(args: CliArgs) {
+    if args.contains(Flag::Help) {
+        printhelp(HELP);
+    }
+
+    let inputfile = match args.inputfile.clone() {
+        Some (i) => i,
+        None => Error::NoInputFile::<&str>.throw(),
+    };
+
+    let debug = args.contains(Flag::Debug);
+    let statements = parse_located(Some (inputfile.clone()), debug);
+
+    let mut checker = Checker::new();
+    checker.set_source(&inputfile);
+    checker.check(&statements);
+
+    // Display the inferred dimensions of each variable
+    for (variable, dimensions) in checker.variables() {
+        println!("{}: {}", variable, dimensions);
+    }
+
+    println!();
+
+    for error in checker.errors() {
+        error.report();
+    }
+
+    match checker.errors().len() {
+        0 => println!("No errors found"),
+        n => {
+            println!("Found {} error(s)", n);
+            exit(1);
+        },
+    }
+}
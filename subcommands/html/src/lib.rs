@@ -0,0 +1,77 @@
+//! Defines the HTML subcommand.
+
+use carlotk::prelude::*;
+
+/// Document template, with inline styles
+const TEMPLATE: &str = include_str!("template.html");
+
+/// Help menu
+const HELP: &str = include_str!("../help_html.txt");
+
+/// Provide help to the user
+pub fn helpme() {
+    printhelp(HELP);
+}
+
+pub fn html(args: CliArgs) {
+    let inputfile = args.inputfile.clone();
+    let debug = args.contains(Flag::Debug);
+
+    let mut outputfile = match inputfile.clone() {
+        Some (f) => f,
+        _ => Error::NoInputFile::<&str>.throw(),
+    };
+
+    // Get settings from options, then from front matter, then from project configuration
+    let front = front_matter(inputfile.clone());
+    let config = project_config(inputfile.clone());
+    let field = |name: &str| args.option(name)
+        .or(front.get(name))
+        .or(config.get(name))
+        .map(|v| v.to_string());
+
+    let title = field("title").unwrap_or_else(|| outputfile.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+    );
+
+    let byline = [field("author"), field("date")].into_iter()
+        .flatten()
+        .map(|s| escape_html(&s))
+        .collect::<Vec<String>>()
+        .join(" &middot; ");
+
+    // Parse and evaluate code
+    let statements = parse_located(inputfile.clone(), debug);
+
+    // Mismatched dimensions are errors unless checking is disabled
+    if !args.contains(Flag::NoCheck) {
+        check_program(&statements, inputfile.as_deref());
+    }
+
+    let expressions = statements.into_iter()
+        .map(|(_, e)| e)
+        .collect::<Vec<Expression>>();
+
+    let mut env = Environment::new();
+    env.set_exact(args.contains(Flag::Exact));
+    if let Some (f) = &inputfile {
+        env.set_source(f);
+    }
+    if let Some (u) = field("units") {
+        env.set_unit_system(&u);
+    }
+    let body = env.html_evaluate(&expressions);
+
+    let output = TEMPLATE.replace("{{title}}", &escape_html(&title))
+        .replace("{{byline}}", &if byline.is_empty() { byline } else { format!("<p>{}</p>\n", byline) })
+        .replace("{{body}}", &body);
+
+    // Write to output file
+    outputfile.set_extension("html");
+    if fs::write(&outputfile, output).is_err() {
+        Error::CouldNotWriteFile (outputfile.display()).throw();
+    }
+
+    println!("\nOutput written to {}", outputfile.display());
+}
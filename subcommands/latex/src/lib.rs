@@ -1,12 +1,24 @@
 //! Defines the LATEX subcommand.
 
-use carlotk::prelude::*;
+mod pdf;
+
+use std::{
+    io::{
+        IsTerminal,
+        stdin,
+    },
+    path::Path,
+};
 
-/// LaTeX header
-const HEADER: &str = include_str!("latex_header.tex");
+use carlotk::prelude::*;
 
-/// LaTeX footer
-const FOOTER: &str = include_str!("latex_footer.tex");
+/// Built-in LaTeX templates, the first of which is the default
+const TEMPLATES: [(&str, &str); 4] = [
+    ("article", include_str!("templates/article.tex")),
+    ("report", include_str!("templates/report.tex")),
+    ("calc-sheet", include_str!("templates/calc-sheet.tex")),
+    ("beamer", include_str!("templates/beamer.tex")),
+];
 
 /// Help menu
 const HELP: &str = include_str!("../help_latex.txt");
@@ -20,57 +32,261 @@ pub fn latex(args: CliArgs) {
     let inputfile = args.inputfile.clone();
     let debug = args.contains(Flag::Debug);
 
-    // Create input method
-    let mut rl = DefaultEditor::new().unwrap();
+    // Get settings from options, then from front matter, then from project configuration
+    let front = front_matter(inputfile.clone());
+    let config = project_config(inputfile.clone());
+    let field = |name: &str| args.option(name)
+        .or(front.get(name))
+        .or(config.get(name))
+        .map(|v| v.to_string());
 
-    // Get title
-    let title = match rl.readline("Title >> ") {
-        Ok (r) => r,
-        Err (_) => Error::CouldNotReadLine ("Title").throw(),
+    // Paths given as options are relative to the current directory, and paths from
+    // front matter or project configuration are relative to the source file
+    let directory = |name: &str| match args.option(name) {
+        Some (_) => PathBuf::new(),
+        None => inputfile.as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
     };
+    let path = |name: &str| field(name).map(|v| directory(name).join(v));
 
-    // Get author
-    let author = match rl.readline("Author >> ") {
-        Ok (r) => r,
-        Err (_) => Error::CouldNotReadLine ("Author").throw(),
-    };
+    let mut title = field("title");
+    let mut author = field("author");
+    let date = field("date");
 
-    let mut output = String::new();
+    // Prompt only if nothing was provided and stdin is interactive
+    if title.is_none() && author.is_none() && date.is_none() && stdin().is_terminal() {
+        // Create input method
+        let mut rl = DefaultEditor::new().unwrap();
+
+        title = match rl.readline("Title >> ") {
+            Ok (r) => Some (r),
+            Err (_) => Error::CouldNotReadLine ("Title").throw(),
+        };
+
+        author = match rl.readline("Author >> ") {
+            Ok (r) => Some (r),
+            Err (_) => Error::CouldNotReadLine ("Author").throw(),
+        };
+    }
 
     let mut outputfile = match inputfile.clone() {
         Some (f) => f,
         _ => Error::NoInputFile::<&str>.throw(),
     };
 
-    // Output header
-    output.push_str(HEADER);
+    // Get template and preamble
+    let beamer = field("template").as_deref() == Some ("beamer");
+    let template = match field("template") {
+        Some (t) => template(&t, &directory("template")),
+        None => TEMPLATES[0].1.to_string(),
+    };
 
-    // Output title and author
-    output.push_str(&format!(
-        "\\title{{\\textbf{{{}}}}}\n",
-        title,
-    ));
-    output.push_str(&format!(
-        "\\author{{{}}}\n",
-        author,
-    ));
-    output.push_str("\\maketitle\n");
-    output.push_str("\\tableofcontents\n");
+    let mut preamble = match path("preamble") {
+        Some (p) => match fs::read_to_string(&p) {
+            Ok (p) => p,
+            Err (_) => Error::CouldNotFindFile (p.display()).throw(),
+        },
+        None => String::new(),
+    };
+
+    let toc = !args.contains(Flag::NoToc) && !matches!(field("toc").as_deref(), Some ("false" | "no" | "off"));
+
+    // Load siunitx before the user's preamble, rounding to the given significant figures
+    let siunitx = args.contains(Flag::Siunitx) || matches!(field("siunitx").as_deref(), Some ("true" | "yes" | "on"));
+    if siunitx {
+        let precision = match field("precision") {
+            Some (p) => match p.parse::<usize>() {
+                Ok (p) => format!("\\sisetup{{round-mode = figures, round-precision = {}}}\n", p),
+                Err (_) => Error::InvalidOptionValue ("precision", p.as_str()).throw(),
+            },
+            None => String::new(),
+        };
+
+        preamble = format!("\\usepackage{{siunitx}}\n{}{}", precision, preamble);
+    }
+
+    // Check the LaTeX engine before writing anything
+    let pdf = args.contains(Flag::Pdf) || matches!(field("pdf").as_deref(), Some ("true" | "yes" | "on"));
+    let engine = field("engine").unwrap_or(pdf::ENGINES[0].to_string());
+    if pdf && !pdf::ENGINES.contains(&engine.as_str()) {
+        Error::InvalidOptionValue ("engine", engine.as_str()).throw();
+    }
 
     // Parse and evaluate code
-    let expressions = parse(inputfile, debug);
+    let statements = parse_located(inputfile.clone(), debug);
+
+    // Mismatched dimensions are errors unless checking is disabled
+    if !args.contains(Flag::NoCheck) {
+        check_program(&statements, inputfile.as_deref());
+    }
+
     let mut env = Environment::new();
-    let latex = env.latex_evaluate(&expressions);
+    env.set_exact(args.contains(Flag::Exact));
+    if let Some (f) = &inputfile {
+        env.set_source(f);
+    }
+    if let Some (u) = field("units") {
+        env.set_unit_system(&u);
+    }
+    env.set_siunitx(siunitx);
+    let (latex, body) = env.latex_evaluate_located(&statements);
+
+    let (latex, body) = if beamer {
+        frames(&latex, &body)
+    } else {
+        (latex, body.into_iter().map(Some).collect())
+    };
+
+    // Fill in template, keeping the source line of each line of the body
+    let output = render(&template, &[
+        ("title", &escape_latex(&title.unwrap_or_default())),
+        ("author", &escape_latex(&author.unwrap_or_default())),
+        ("date", &date.map(|d| escape_latex(&d)).unwrap_or(String::from("\\today"))),
+        ("preamble", &preamble),
+        ("toc", if toc { "\\tableofcontents\n" } else { "" }),
+    ]);
+
+    let mut lines = match output.split_once("{{body}}") {
+        Some ((before, _)) => vec![None; before.matches('\n').count()],
+        None => Vec::new(),
+    };
+    lines.extend(body);
 
-    // Output code
-    output.push_str(&latex);
+    let output = output.replace("{{body}}", &latex);
 
-    // Output footer
-    output.push_str(FOOTER);
+    // Write to output file, in the output directory if there is one
+    if let Some (d) = path("output-dir") {
+        if fs::create_dir_all(&d).is_err() {
+            Error::InvalidOptionValue ("output-dir", &d.display().to_string()).throw();
+        }
 
-    // Write to output file
+        outputfile = d.join(outputfile.file_name().unwrap_or_default());
+    }
     outputfile.set_extension("tex");
-    let _ = fs::write(&outputfile, output);
+    if fs::write(&outputfile, output).is_err() {
+        Error::CouldNotWriteFile (outputfile.display()).throw();
+    }
 
     println!("\nOutput written to {}", outputfile.display());
-}
\ No newline at end of file
+
+    // Build a PDF with a LaTeX engine
+    if pdf {
+        let pdffile = pdf::build(&engine, &outputfile, &inputfile.unwrap_or_default(), &lines);
+
+        println!("PDF written to {}", pdffile.display());
+    }
+}
+
+/// Gets a template by name (e.g. `report`) or from a file in a given directory.
+fn template(name: &str, directory: &Path) -> String {
+    if let Some ((_, t)) = TEMPLATES.iter().find(|(n, _)| *n == name) {
+        return t.to_string();
+    }
+
+    let file = directory.join(name);
+    let template = match fs::read_to_string(&file) {
+        Ok (t) => t,
+        Err (_) => Error::CouldNotFindFile (file.display()).throw(),
+    };
+
+    if !template.contains("{{body}}") {
+        Error::InvalidTemplate (file.display().to_string().as_str(), "missing {{body}} placeholder").warn();
+    }
+
+    template
+}
+
+/// Fills in the `{{name}}` placeholders of a template.
+fn render(template: &str, fields: &[(&str, &str)]) -> String {
+    let mut output = template.to_string();
+
+    for (name, value) in fields {
+        output = output.replace(&format!("{{{{{}}}}}", name), value);
+    }
+
+    output
+}
+
+/// Splits the body of a beamer document into frames, one for each header, with
+/// sections written between frames, keeping the source line of each line of the body.
+fn frames(latex: &str, lines: &[usize]) -> (String, Vec<Option<usize>>) {
+    let mut output = String::new();
+    let mut located = Vec::new();
+
+    let mut push = |text: &str, line: Option<usize>| {
+        output.push_str(text);
+        output.push('\n');
+        located.push(line);
+    };
+
+    // A frame is opened at the first line of content after a header
+    let mut title = None;
+    let mut open = false;
+
+    for (text, line) in latex.lines().zip(lines) {
+        let header = ["\\section{", "\\subsection{", "\\subsubsection{"].iter()
+            .find_map(|h| text.strip_prefix(h))
+            .and_then(|t| t.strip_suffix('}'));
+
+        if let Some (h) = header {
+            if open {
+                push("\\end{frame}", None);
+                open = false;
+            }
+
+            push(text, Some (*line));
+            title = Some (h);
+        } else if !open && !text.trim().is_empty() {
+            match title {
+                Some (t) => push(&format!("\\begin{{frame}}[allowframebreaks]{{{}}}", t), None),
+                None => push("\\begin{frame}[allowframebreaks]", None),
+            }
+
+            push(text, Some (*line));
+            open = true;
+        } else {
+            push(text, Some (*line));
+        }
+    }
+
+    if open {
+        push("\\end{frame}", None);
+    }
+
+    (output, located)
+}
+
+#[test]
+fn test_templates() {
+    // Template files are read from the given directory
+    let directory = std::env::temp_dir().join("carlo-latex-templates");
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("mine.tex"), "\\begin{document}\n{{title}}\n{{body}}\n\\end{document}\n").unwrap();
+
+    let mine = template("mine.tex", &directory);
+    assert_eq!(render(&mine, &[("title", "Beam Design")]), "\\begin{document}\nBeam Design\n{{body}}\n\\end{document}\n");
+
+    // Built-in templates take precedence over files
+    assert_eq!(template("beamer", &directory), TEMPLATES[3].1);
+}
+
+#[test]
+fn test_frames() {
+    let latex = "$$\na := 1\n$$\n\n\\section{Loads}\n\n$$\nw := 2\n$$\n";
+    let lines = [1, 1, 1, 1, 2, 2, 3, 3, 3];
+
+    // Content before the first header is framed without a title
+    let (output, located) = frames(latex, &lines);
+    assert_eq!(output, concat!(
+        "\\begin{frame}[allowframebreaks]\n$$\na := 1\n$$\n\n\\end{frame}\n",
+        "\\section{Loads}\n\n",
+        "\\begin{frame}[allowframebreaks]{Loads}\n$$\nw := 2\n$$\n\\end{frame}\n",
+    ));
+    assert_eq!(located, vec![
+        None, Some (1), Some (1), Some (1), Some (1), None,
+        Some (2), Some (2),
+        None, Some (3), Some (3), Some (3), None,
+    ]);
+}
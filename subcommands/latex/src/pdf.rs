@@ -0,0 +1,85 @@
+//! Builds a PDF from a LaTeX file with a locally installed LaTeX engine.
+
+use std::{
+    path::Path,
+    process::Command,
+};
+
+use carlotk::prelude::*;
+
+/// LaTeX engines that can build a PDF, the first of which is the default
+pub const ENGINES: [&str; 4] = [
+    "pdflatex",
+    "xelatex",
+    "lualatex",
+    "tectonic",
+];
+
+/// Builds a PDF from a LaTeX file in its directory, returning the path of the PDF.
+///
+/// The engine runs twice so that the table of contents is filled in (tectonic
+/// reruns itself as needed). Its output is captured rather than printed, and any
+/// errors are reported at the line of the source file that produced them, given
+/// by `lines` for each line of the LaTeX file, or else at the line of the LaTeX file.
+pub fn build(engine: &str, texfile: &Path, sourcefile: &Path, lines: &[Option<usize>]) -> PathBuf {
+    let directory = match texfile.parent() {
+        Some (d) if d.as_os_str().is_empty() => Path::new("."),
+        Some (d) => d,
+        None => Path::new("."),
+    };
+    let name = texfile.file_name().unwrap_or_default();
+
+    let mut command = Command::new(engine);
+    command.current_dir(directory);
+    let runs = if engine == "tectonic" {
+        command.arg("--keep-logs").arg(name);
+        1
+    } else {
+        command.args(["-interaction=nonstopmode", "-halt-on-error", "-file-line-error"]).arg(name);
+        2
+    };
+
+    for _ in 0..runs {
+        let output = match command.output() {
+            Ok (o) => o,
+            Err (_) => Error::CouldNotRunEngine (engine).throw(),
+        };
+
+        if !output.status.success() {
+            let log = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+
+            for (line, message) in errors(&log, &name.to_string_lossy()) {
+                let location = match lines.get(line.saturating_sub(1)) {
+                    Some (Some (l)) => format!("{}:{}", sourcefile.display(), l),
+                    _ => format!("{}:{}", texfile.display(), line),
+                };
+
+                Error::LatexError (location.as_str(), message.as_str()).report();
+            }
+
+            Error::FailedBuild (engine, &texfile.with_extension("log").display().to_string()).throw();
+        }
+    }
+
+    texfile.with_extension("pdf")
+}
+
+/// Finds the errors in the output of a LaTeX engine, each with the line of the
+/// LaTeX file at which it occurred (e.g. `./beam.tex:12: Undefined control sequence.`).
+fn errors(output: &str, name: &str) -> Vec<(usize, String)> {
+    let mut errors = Vec::new();
+
+    for line in output.lines() {
+        let error = line.split_once(&format!("{}:", name))
+            .and_then(|(_, rest)| rest.split_once(':'))
+            .and_then(|(n, message)| Some ((n.parse::<usize>().ok()?, message.trim().to_string())));
+
+        if let Some (e) = error {
+            if !errors.contains(&e) {
+                errors.push(e);
+            }
+        }
+    }
+
+    errors
+}
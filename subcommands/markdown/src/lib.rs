@@ -0,0 +1,55 @@
+//! Defines the MARKDOWN subcommand.
+
+use carlotk::prelude::*;
+
+/// Help menu
+const HELP: &str = include_str!("../help_markdown.txt");
+
+/// Provide help to the user
+pub fn helpme() {
+    printhelp(HELP);
+}
+
+pub fn markdown(args: CliArgs) {
+    if args.contains(Flag::Help) {
+        printhelp(HELP);
+    }
+
+    let inputfile = args.inputfile.clone();
+    let debug = args.contains(Flag::Debug);
+
+    let mut outputfile = match inputfile.clone() {
+        Some (f) => f,
+        _ => Error::NoInputFile::<&str>.throw(),
+    };
+
+    // Parse and evaluate code
+    let statements = parse_located(inputfile.clone(), debug);
+
+    // Mismatched dimensions are errors unless checking is disabled
+    if !args.contains(Flag::NoCheck) {
+        check_program(&statements, inputfile.as_deref());
+    }
+
+    let expressions = statements.into_iter()
+        .map(|(_, e)| e)
+        .collect::<Vec<Expression>>();
+
+    let mut env = Environment::new();
+    env.set_exact(args.contains(Flag::Exact));
+    if let Some (f) = &inputfile {
+        env.set_source(f);
+    }
+    if let Some (u) = args.option("units") {
+        env.set_unit_system(u);
+    }
+    let output = env.markdown_evaluate(&expressions);
+
+    // Write to output file
+    outputfile.set_extension("md");
+    if fs::write(&outputfile, output).is_err() {
+        Error::CouldNotWriteFile (outputfile.display()).throw();
+    }
+
+    println!("\nOutput written to {}", outputfile.display());
+}
@@ -30,16 +30,35 @@ pub fn repl(args: CliArgs) {
 
     // Begin environment
     let mut env = Environment::new();
+    env.set_exact(args.contains(Flag::Exact));
+    if let Some (u) = args.option("units") {
+        env.set_unit_system(u);
+    }
 
     // Import file
     if let Some(i) = &inputfile {
         println!("Importing {}", i.display());
         println!();
+        env.set_source(i);
+    }
+    let imported = parse_located(inputfile.clone(), debug);
+
+    // Mismatched dimensions are errors unless checking is disabled
+    let check = !args.contains(Flag::NoCheck);
+    let mut checker = Checker::new();
+    if let Some (f) = &inputfile {
+        checker.set_source(f);
+    }
+    if check {
+        check_program(&imported, inputfile.as_deref());
+        checker.check(&imported);
     }
-    let imported = parse(inputfile, debug);
 
     // Evaluated imported file
-    env.evaluate(&imported);
+    env.evaluate(&imported.into_iter()
+        .map(|(_, e)| e)
+        .collect::<Vec<Expression>>()
+    );
 
     // Begin REPL
     let mut rl = DefaultEditor::new().unwrap();
@@ -54,12 +73,38 @@ pub fn repl(args: CliArgs) {
                 let _ = rl.add_history_entry(r.as_str());
                 r
             },
+            Err (ReadlineError::Eof | ReadlineError::Interrupted) => break,
             Err (_) => Error::CouldNotReadLine (i).throw(),
         };
         println!();
 
+        // Session commands (e.g. `:display sig 3`) are pragmas
+        let readline = match readline.trim().strip_prefix(':') {
+            Some (command) => format!("#pragma {}", command),
+            None => readline,
+        };
+
         // Parse input
-        let expr = parser.parse(&readline);
+        let statements = parser.parse_located(&readline);
+
+        // Inputs with mismatched dimensions are not evaluated
+        if check {
+            let found = checker.errors().len();
+            checker.check(&statements);
+
+            if checker.errors().len() > found {
+                for error in &checker.errors()[found..] {
+                    error.report();
+                }
+
+                i += 1;
+                continue;
+            }
+        }
+
+        let expr = statements.into_iter()
+            .map(|(_, e)| e)
+            .collect::<Vec<Expression>>();
         
         // Evaluate input
         let output = env.evaluate(&expr);
@@ -1,5 +1,7 @@
 //! Defines the RUN subcommand.
 
+use std::process::exit;
+
 use carlotk::prelude::*;
 
 const HELP: &str = include_str!("../help_run.txt");
@@ -16,8 +18,30 @@ pub fn run(args: CliArgs) {
 
     let inputfile = args.inputfile.clone();
     let debug = args.contains(Flag::Debug);
-    let expressions = parse(inputfile, debug);
+    let statements = parse_located(inputfile.clone(), debug);
+
+    // Mismatched dimensions are errors unless checking is disabled
+    if !args.contains(Flag::NoCheck) {
+        check_program(&statements, inputfile.as_deref());
+    }
+
+    let expressions = statements.into_iter()
+        .map(|(_, e)| e)
+        .collect::<Vec<Expression>>();
+
     let mut env = Environment::new();
+    env.set_exact(args.contains(Flag::Exact));
+    if let Some (f) = &inputfile {
+        env.set_source(f);
+    }
+    if let Some (u) = args.option("units") {
+        env.set_unit_system(u);
+    }
     let output = env.evaluate(&expressions);
     println!("{}", output);
+
+    // Errors found while evaluating (e.g. an unreadable data file) fail the run
+    if !env.errors().is_empty() {
+        exit(1);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+//! Defines the TYPST subcommand.
+
+use carlotk::prelude::*;
+
+/// Document template
+const TEMPLATE: &str = include_str!("template.typ");
+
+/// Help menu
+const HELP: &str = include_str!("../help_typst.txt");
+
+/// Provide help to the user
+pub fn helpme() {
+    printhelp(HELP);
+}
+
+pub fn typst(args: CliArgs) {
+    let inputfile = args.inputfile.clone();
+    let debug = args.contains(Flag::Debug);
+
+    let mut outputfile = match inputfile.clone() {
+        Some (f) => f,
+        _ => Error::NoInputFile::<&str>.throw(),
+    };
+
+    // Get settings from options, then from front matter, then from project configuration
+    let front = front_matter(inputfile.clone());
+    let config = project_config(inputfile.clone());
+    let field = |name: &str| args.option(name)
+        .or(front.get(name))
+        .or(config.get(name))
+        .map(|v| v.to_string());
+
+    let title = field("title").map(|t| escape_typst(&t)).unwrap_or_default();
+    let author = field("author").map(|a| escape_typst(&a)).unwrap_or_default();
+    let date = match field("date") {
+        Some (d) => escape_typst(&d),
+        None => String::from("#datetime.today().display()"),
+    };
+
+    let toc = !args.contains(Flag::NoToc) && !matches!(field("toc").as_deref(), Some ("false" | "no" | "off"));
+
+    // Parse and evaluate code
+    let statements = parse_located(inputfile.clone(), debug);
+
+    // Mismatched dimensions are errors unless checking is disabled
+    if !args.contains(Flag::NoCheck) {
+        check_program(&statements, inputfile.as_deref());
+    }
+
+    let expressions = statements.into_iter()
+        .map(|(_, e)| e)
+        .collect::<Vec<Expression>>();
+
+    let mut env = Environment::new();
+    env.set_exact(args.contains(Flag::Exact));
+    if let Some (f) = &inputfile {
+        env.set_source(f);
+    }
+    if let Some (u) = field("units") {
+        env.set_unit_system(&u);
+    }
+    let body = env.typst_evaluate(&expressions);
+
+    let output = TEMPLATE.replace("{{title}}", &title)
+        .replace("{{author}}", &author)
+        .replace("{{date}}", &date)
+        .replace("{{toc}}", if toc { "#outline()\n\n" } else { "" })
+        .replace("{{body}}", &body);
+
+    // Write to output file
+    outputfile.set_extension("typ");
+    if fs::write(&outputfile, output).is_err() {
+        Error::CouldNotWriteFile (outputfile.display()).throw();
+    }
+
+    println!("\nOutput written to {}", outputfile.display());
+}
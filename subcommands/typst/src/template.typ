@@ -0,0 +1,13 @@
+#set page(margin: 1in, numbering: "1")
+#set text(size: 12pt)
+#set heading(numbering: "1.1")
+
+#align(center)[
+  #text(size: 17pt)[*{{title}}*]
+
+  {{author}}
+
+  {{date}}
+]
+
+{{toc}}{{body}}